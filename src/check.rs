@@ -0,0 +1,793 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use crate::{
+    Algorithm, Blake3Kind, Blake3Mode, State, filename, hash_argument, print_bytes, print_error,
+    print_hash_error,
+};
+use hashsum::hash_algorithm::digest::Digest;
+use std::{
+    borrow::Cow,
+    ffi::{OsStr, OsString},
+    fs::File,
+    io::{self, BufRead, BufReader, ErrorKind},
+};
+
+/// A single parsed line of a checksum file.
+#[derive(Debug, PartialEq)]
+struct ChecksumLine {
+    algorithm: Algorithm,
+    /// Digest length in bits, or None for the algorithm default.
+    output_bits: Option<usize>,
+    /// True if the digest is an HMAC, which needs '--hmac-key-file' to check.
+    hmac: bool,
+    /// BLAKE3 mode of the digest, which needs '--key-file' or '--derive-key' to check unless it
+    /// is a plain hash.
+    blake3: Blake3Kind,
+    digest: Digest,
+    filename: OsString,
+}
+
+/// Options controlling what '--check' prints and which problems fail it.
+#[derive(Default)]
+pub struct CheckOptions {
+    /// Don't print a line for files that matched, from '--quiet'.
+    pub quiet: bool,
+    /// Print nothing but errors, leaving the exit status to report the result, from '--status'.
+    pub status: bool,
+    /// Warn about each improperly formatted line, from '--warn'.
+    pub warn: bool,
+    /// Fail if any line is improperly formatted, from '--strict'.
+    pub strict: bool,
+    /// Skip listed files that don't exist rather than failing, from '--ignore-missing'.
+    pub ignore_missing: bool,
+}
+
+/// Running totals for a check run, used to print the closing warnings.
+#[derive(Default)]
+struct CheckSummary {
+    improperly_formatted: usize,
+    unreadable: usize,
+    mismatched: usize,
+}
+
+/// Splits `bytes` around the first occurrence of `delimiter`.
+fn split_once<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let index = bytes
+        .windows(delimiter.len())
+        .position(|window| window == delimiter)?;
+    Some((&bytes[..index], &bytes[index + delimiter.len()..]))
+}
+
+/// Splits `bytes` around the last occurrence of `delimiter`.
+fn rsplit_once<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Option<(&'a [u8], &'a [u8])> {
+    let index = bytes
+        .windows(delimiter.len())
+        .rposition(|window| window == delimiter)?;
+    Some((&bytes[..index], &bytes[index + delimiter.len()..]))
+}
+
+/// Decodes the file name of a line, which was escaped if the line started with a backslash.
+fn decode_filename(filename: &[u8], escaped: bool) -> Option<OsString> {
+    let filename = if escaped {
+        Cow::Owned(filename::unescape(filename)?)
+    } else {
+        Cow::Borrowed(filename)
+    };
+
+    filename::from_bytes(&filename)
+}
+
+/// Decodes a hex or base64 digest, returning None unless it is the correct length for
+/// `algorithm` producing `output_bits` bits.
+fn decode_digest(
+    digest: &[u8],
+    algorithm: Algorithm,
+    output_bits: Option<usize>,
+) -> Option<Digest> {
+    let digest = str::from_utf8(digest).ok()?;
+    let digest_length = algorithm.digest_length(output_bits);
+    // Hex and padded base64 have the same length for 4 byte digests, but the padding is never
+    // valid hex.
+    let decoded = if digest.len() == 2 * digest_length {
+        Digest::from_hex(digest).or_else(|| Digest::from_base64(digest))
+    } else {
+        Digest::from_base64(digest)
+    };
+
+    decoded.filter(|digest| digest.len() == digest_length)
+}
+
+/// Parses a BSD-style line in the form 'MD5 (filename) = digest', where HMACs are tagged as in
+/// 'HMAC-MD5' and keyed BLAKE3 digests as in 'BLAKE3-KEYED'.
+fn parse_tagged_line(line: &[u8], escaped: bool) -> Option<ChecksumLine> {
+    let (tag, rest) = split_once(line, b" (")?;
+    let tag = str::from_utf8(tag).ok()?;
+    let (tag, hmac) = match tag.strip_prefix("HMAC-") {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+    let (algorithm, output_bits, blake3) = Algorithm::from_tag_with_length(tag)?;
+    if hmac && !algorithm.supports_hmac() {
+        return None;
+    }
+    // Search from the right as the filename itself may contain ') = '.
+    let (filename, digest) = rsplit_once(rest, b") = ")?;
+    let digest = decode_digest(digest, algorithm, output_bits)?;
+
+    Some(ChecksumLine {
+        algorithm,
+        output_bits,
+        hmac,
+        blake3,
+        digest,
+        filename: decode_filename(filename, escaped)?,
+    })
+}
+
+/// Parses an untagged line in the form 'digest  filename' or 'digest *filename'. A single
+/// separating space is also accepted.
+fn parse_untagged_line(
+    line: &[u8],
+    escaped: bool,
+    algorithm: Algorithm,
+    output_bits: Option<usize>,
+    hmac: bool,
+    blake3: Blake3Kind,
+) -> Option<ChecksumLine> {
+    // The default CRC has no checkable format, so untagged lines need an algorithm selected.
+    if algorithm.is_legacy_checksum() {
+        return None;
+    }
+
+    let (digest, rest) = split_once(line, b" ")?;
+    let filename = match rest.first() {
+        Some(b' ' | b'*') => &rest[1..],
+        _ => rest,
+    };
+
+    if filename.is_empty() {
+        return None;
+    }
+    let digest = decode_digest(digest, algorithm, output_bits)?;
+
+    Some(ChecksumLine {
+        algorithm,
+        output_bits,
+        hmac,
+        blake3,
+        digest,
+        filename: decode_filename(filename, escaped)?,
+    })
+}
+
+/// Parses a checksum line, falling back to `default_algorithm` producing `default_output_bits`
+/// bits for untagged lines, which are HMACs if `default_hmac` is set and made in the BLAKE3 mode
+/// `default_blake3`. A line starting with a backslash has an escaped file name.
+fn parse_line(
+    line: &[u8],
+    default_algorithm: Algorithm,
+    default_output_bits: Option<usize>,
+    default_hmac: bool,
+    default_blake3: Blake3Kind,
+) -> Option<ChecksumLine> {
+    let (line, escaped) = match line.strip_prefix(b"\\") {
+        Some(line) => (line, true),
+        None => (line, false),
+    };
+
+    parse_tagged_line(line, escaped).or_else(|| {
+        parse_untagged_line(
+            line,
+            escaped,
+            default_algorithm,
+            default_output_bits,
+            default_hmac,
+            default_blake3,
+        )
+    })
+}
+
+/// Prints the outcome of checking `filename`, escaping the name as in checksum files.
+fn print_status(filename: &OsStr, status: &str) {
+    let name = filename.as_encoded_bytes();
+    let escaped_name = filename::escape(name);
+    let escape_prefix: &[u8] = if escaped_name.is_some() { b"\\" } else { b"" };
+    let name = escaped_name.as_deref().unwrap_or(name);
+
+    print_bytes(&[escape_prefix, name, b": ", status.as_bytes(), b"\n"].concat());
+}
+
+fn print_summary(summary: &CheckSummary) {
+    if summary.improperly_formatted == 1 {
+        eprintln!("hashsum: WARNING: 1 line is improperly formatted");
+    } else if summary.improperly_formatted > 1 {
+        eprintln!(
+            "hashsum: WARNING: {} lines are improperly formatted",
+            summary.improperly_formatted
+        );
+    }
+
+    if summary.unreadable == 1 {
+        eprintln!("hashsum: WARNING: 1 listed file could not be read");
+    } else if summary.unreadable > 1 {
+        eprintln!(
+            "hashsum: WARNING: {} listed files could not be read",
+            summary.unreadable
+        );
+    }
+
+    if summary.mismatched == 1 {
+        eprintln!("hashsum: WARNING: 1 computed checksum did NOT match");
+    } else if summary.mismatched > 1 {
+        eprintln!(
+            "hashsum: WARNING: {} computed checksums did NOT match",
+            summary.mismatched
+        );
+    }
+}
+
+/// Verifies a single parsed line against the file it names, printing the outcome. Returns false
+/// if the file was missing and skipped with '--ignore-missing'.
+fn check_line(line: &ChecksumLine, state: &State, summary: &mut CheckSummary) -> bool {
+    let options = &state.check_options;
+    let hmac_key = if line.hmac {
+        state.hmac_key.as_deref()
+    } else {
+        None
+    };
+    let blake3_mode = if line.blake3 == Blake3Kind::Hash {
+        &Blake3Mode::Hash
+    } else {
+        &state.blake3_mode
+    };
+
+    match hash_argument(
+        &[(line.algorithm, line.output_bits)],
+        blake3_mode,
+        hmac_key,
+        state.tree_digest,
+        state.buffer_size,
+        state.jobs,
+        &line.filename,
+    ) {
+        Ok((digests, _)) if digests[0] == line.digest => {
+            if !options.quiet && !options.status {
+                print_status(&line.filename, "OK");
+            }
+        }
+        Ok(_) => {
+            if !options.status {
+                print_status(&line.filename, "FAILED");
+            }
+            summary.mismatched += 1;
+        }
+        Err(e) if options.ignore_missing && e.kind() == ErrorKind::NotFound => return false,
+        Err(e) => {
+            print_hash_error(state, &line.filename, e);
+            if !options.status {
+                print_status(&line.filename, "FAILED open or read");
+            }
+            summary.unreadable += 1;
+        }
+    }
+
+    true
+}
+
+/// Reads every line of a checksum file and verifies the files listed in it. Returns false if
+/// the checksum file could not be read, contained no valid lines or, with '--ignore-missing',
+/// listed no file that exists.
+fn check_stream(
+    name: &str,
+    stream: impl BufRead,
+    state: &State,
+    summary: &mut CheckSummary,
+) -> bool {
+    let mut valid_lines = 0;
+    let mut verified_files = 0;
+
+    for (line_number, line) in (1..).zip(stream.split(b'\n')) {
+        let line = match line {
+            Ok(f) => f,
+            Err(e) => {
+                print_error(name, e);
+                return false;
+            }
+        };
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+
+        // Lines starting with '#' are comments.
+        if line.starts_with(b"#") {
+            continue;
+        }
+
+        let default_hmac = state.hmac_key.is_some();
+        let default_blake3 = state.blake3_mode.kind();
+        // Only one algorithm can be selected when checking.
+        let (default_algorithm, default_output_bits) = state.algorithms[0];
+        match parse_line(
+            line,
+            default_algorithm,
+            default_output_bits,
+            default_hmac,
+            default_blake3,
+        ) {
+            // HMAC lines can't be checked without the key, nor keyed BLAKE3 lines without the
+            // matching mode.
+            Some(checksum_line)
+                if (!checksum_line.hmac || default_hmac)
+                    && (checksum_line.blake3 == Blake3Kind::Hash
+                        || checksum_line.blake3 == default_blake3) =>
+            {
+                valid_lines += 1;
+                if check_line(&checksum_line, state, summary) {
+                    verified_files += 1;
+                }
+            }
+            _ => {
+                summary.improperly_formatted += 1;
+                if state.check_options.warn {
+                    eprintln!("hashsum: {name}: {line_number}: improperly formatted checksum line");
+                }
+            }
+        }
+    }
+
+    if valid_lines == 0 {
+        eprintln!("hashsum: {name}: no properly formatted checksum lines found");
+        return false;
+    }
+    if verified_files == 0 {
+        eprintln!("hashsum: {name}: no file was verified");
+        return false;
+    }
+
+    true
+}
+
+/// Checks every checksum file passed as an argument, or stdin if there are none and they
+/// weren't read from a list. Returns true only if every listed file was read and matched its
+/// checksum.
+pub fn check_files(state: &State) -> bool {
+    let mut success = true;
+    let mut summary = CheckSummary::default();
+    let stdin_argument = vec![Ok(OsString::from("-"))];
+    let arguments = if state.arguments.is_empty() && !state.files_from_list {
+        &stdin_argument
+    } else {
+        &state.arguments
+    };
+
+    for argument in arguments {
+        let argument = match argument {
+            Ok(argument) => argument,
+            Err(entry) => {
+                print_error(&entry.location, entry.reason);
+                success = false;
+                continue;
+            }
+        };
+        let checked = if argument == "-" {
+            check_stream("standard input", io::stdin().lock(), state, &mut summary)
+        } else {
+            match File::open(argument) {
+                Ok(f) => check_stream(
+                    &argument.to_string_lossy(),
+                    BufReader::new(f),
+                    state,
+                    &mut summary,
+                ),
+                Err(e) => {
+                    print_error(argument.display(), e);
+                    false
+                }
+            }
+        };
+
+        success &= checked;
+    }
+
+    if !state.check_options.status {
+        print_summary(&summary);
+    }
+
+    success
+        && summary.unreadable == 0
+        && summary.mismatched == 0
+        && !(state.check_options.strict && summary.improperly_formatted > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tagged_line() {
+        assert_eq!(
+            parse_line(
+                b"MD5 (file) = d41d8cd98f00b204e9800998ecf8427e",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            Some(ChecksumLine {
+                algorithm: Algorithm::MD5,
+                output_bits: None,
+                hmac: false,
+                blake3: Blake3Kind::Hash,
+                digest: Digest::from_hex("d41d8cd98f00b204e9800998ecf8427e").unwrap(),
+                filename: OsString::from("file"),
+            })
+        );
+
+        assert_eq!(
+            parse_line(
+                b"SHA1 (a (b) = c) = da39a3ee5e6b4b0d3255bfef95601890afd80709",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            Some(ChecksumLine {
+                algorithm: Algorithm::SHA1,
+                output_bits: None,
+                hmac: false,
+                blake3: Blake3Kind::Hash,
+                digest: Digest::from_hex("da39a3ee5e6b4b0d3255bfef95601890afd80709").unwrap(),
+                filename: OsString::from("a (b) = c"),
+            })
+        );
+    }
+
+    #[test]
+    fn untagged_line() {
+        let expected = Some(ChecksumLine {
+            algorithm: Algorithm::MD5,
+            output_bits: None,
+            hmac: false,
+            blake3: Blake3Kind::Hash,
+            digest: Digest::from_hex("d41d8cd98f00b204e9800998ecf8427e").unwrap(),
+            filename: OsString::from("file name"),
+        });
+
+        assert_eq!(
+            parse_line(
+                b"d41d8cd98f00b204e9800998ecf8427e  file name",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_line(
+                b"d41d8cd98f00b204e9800998ecf8427e *file name",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_line(
+                b"d41d8cd98f00b204e9800998ecf8427e file name",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn base64_line() {
+        let expected = Some(ChecksumLine {
+            algorithm: Algorithm::MD5,
+            output_bits: None,
+            hmac: false,
+            blake3: Blake3Kind::Hash,
+            digest: Digest::from_hex("d41d8cd98f00b204e9800998ecf8427e").unwrap(),
+            filename: OsString::from("file"),
+        });
+
+        assert_eq!(
+            parse_line(
+                b"MD5 (file) = 1B2M2Y8AsgTpgAmY7PhCfg==",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_line(
+                b"1B2M2Y8AsgTpgAmY7PhCfg==  file",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        // Base64 digest of the wrong length for the selected algorithm.
+        assert_eq!(
+            parse_line(
+                b"1B2M2Y8AsgTpgAmY7PhCfg==  file",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn improperly_formatted_line() {
+        // Digest length doesn't match the selected algorithm.
+        assert_eq!(
+            parse_line(
+                b"d41d8cd98f00b204e9800998ecf8427e  file",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+        // Unknown tag.
+        assert_eq!(
+            parse_line(
+                b"FOO (file) = d41d8cd98f00b204e9800998ecf8427e",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+        // Non hex digest.
+        assert_eq!(
+            parse_line(
+                b"z41d8cd98f00b204e9800998ecf8427e  file",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+        // Missing filename.
+        assert_eq!(
+            parse_line(
+                b"d41d8cd98f00b204e9800998ecf8427e  ",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+        assert_eq!(
+            parse_line(b"", Algorithm::MD5, None, false, Blake3Kind::Hash),
+            None
+        );
+    }
+
+    #[test]
+    fn variable_length_line() {
+        assert_eq!(
+            parse_line(
+                b"SHAKE128-8 (file) = 7f",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            Some(ChecksumLine {
+                algorithm: Algorithm::SHAKE128,
+                output_bits: Some(8),
+                hmac: false,
+                blake3: Blake3Kind::Hash,
+                digest: Digest::from_hex("7f").unwrap(),
+                filename: OsString::from("file"),
+            })
+        );
+        assert_eq!(
+            parse_line(
+                b"7f  file",
+                Algorithm::SHAKE128,
+                Some(8),
+                false,
+                Blake3Kind::Hash
+            ),
+            Some(ChecksumLine {
+                algorithm: Algorithm::SHAKE128,
+                output_bits: Some(8),
+                hmac: false,
+                blake3: Blake3Kind::Hash,
+                digest: Digest::from_hex("7f").unwrap(),
+                filename: OsString::from("file"),
+            })
+        );
+        // Lengths must be a multiple of 8 and only apply to variable length algorithms.
+        assert_eq!(
+            parse_line(
+                b"SHAKE128-12 (file) = 7f",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+        assert_eq!(
+            parse_line(
+                b"SHA3-256-8 (file) = 7f",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn keyed_blake3_line() {
+        let digest = "157f8b4b104070014ab0b3b7aff364f794e010e92b1c976318e892f380b53406";
+        for (tag, blake3) in [
+            ("BLAKE3-KEYED", Blake3Kind::KeyedHash),
+            ("BLAKE3-DERIVE", Blake3Kind::DeriveKey),
+        ] {
+            assert_eq!(
+                parse_line(
+                    format!("{tag} (file) = {digest}").as_bytes(),
+                    Algorithm::MD5,
+                    None,
+                    false,
+                    Blake3Kind::Hash,
+                ),
+                Some(ChecksumLine {
+                    algorithm: Algorithm::BLAKE3,
+                    output_bits: None,
+                    hmac: false,
+                    blake3,
+                    digest: Digest::from_hex(digest).unwrap(),
+                    filename: OsString::from("file"),
+                })
+            );
+        }
+
+        assert_eq!(
+            parse_line(
+                b"BLAKE3-KEYED-8 (file) = 7f",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash,
+            )
+            .map(|line| (line.output_bits, line.blake3)),
+            Some((Some(8), Blake3Kind::KeyedHash))
+        );
+        // Untagged lines are in the mode selected by the options.
+        assert_eq!(
+            parse_line(
+                format!("{digest}  file").as_bytes(),
+                Algorithm::BLAKE3,
+                None,
+                false,
+                Blake3Kind::KeyedHash,
+            )
+            .map(|line| line.blake3),
+            Some(Blake3Kind::KeyedHash)
+        );
+    }
+
+    #[test]
+    fn hmac_line() {
+        let expected = Some(ChecksumLine {
+            algorithm: Algorithm::MD5,
+            output_bits: None,
+            hmac: true,
+            blake3: Blake3Kind::Hash,
+            digest: Digest::from_hex("d9bf7c3a63eae7031c4e6d7c9b78ba93").unwrap(),
+            filename: OsString::from("file"),
+        });
+
+        assert_eq!(
+            parse_line(
+                b"HMAC-MD5 (file) = d9bf7c3a63eae7031c4e6d7c9b78ba93",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_line(
+                b"d9bf7c3a63eae7031c4e6d7c9b78ba93  file",
+                Algorithm::MD5,
+                None,
+                true,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        // Checksums can't be used in an HMAC.
+        assert_eq!(
+            parse_line(
+                b"HMAC-CRC32C (file) = 364b3fb7",
+                Algorithm::MD5,
+                None,
+                true,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn escaped_line() {
+        let expected = Some(ChecksumLine {
+            algorithm: Algorithm::MD5,
+            output_bits: None,
+            hmac: false,
+            blake3: Blake3Kind::Hash,
+            digest: Digest::from_hex("d41d8cd98f00b204e9800998ecf8427e").unwrap(),
+            filename: OsString::from("a\nb\\c"),
+        });
+
+        assert_eq!(
+            parse_line(
+                b"\\MD5 (a\\nb\\\\c) = d41d8cd98f00b204e9800998ecf8427e",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        assert_eq!(
+            parse_line(
+                b"\\d41d8cd98f00b204e9800998ecf8427e  a\\nb\\\\c",
+                Algorithm::MD5,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            expected
+        );
+        // Without the leading backslash the name is taken as it is.
+        assert_eq!(
+            parse_line(
+                b"MD5 (a\\nb) = d41d8cd98f00b204e9800998ecf8427e",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            )
+            .unwrap()
+            .filename,
+            OsString::from("a\\nb")
+        );
+        // Unknown escape sequences make the line improperly formatted.
+        assert_eq!(
+            parse_line(
+                b"\\MD5 (a\\tb) = d41d8cd98f00b204e9800998ecf8427e",
+                Algorithm::SHA1,
+                None,
+                false,
+                Blake3Kind::Hash
+            ),
+            None
+        );
+    }
+}
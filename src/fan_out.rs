@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use std::{
+    io::{self, ErrorKind, Read},
+    sync::{
+        Arc,
+        mpsc::{self, Receiver},
+    },
+    thread,
+};
+
+// Chunks each consumer may fall behind the reader by before the reader waits for it, bounding
+// the memory held for slow consumers.
+const CHANNEL_DEPTH: usize = 4;
+
+/// Reader over the chunks of a stream sent by `fan_out`, ending once the sender hangs up.
+struct ChunkReader {
+    receiver: Receiver<Arc<[u8]>>,
+    chunk: Arc<[u8]>,
+    /// Position within the current chunk.
+    position: usize,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let bytes = buffer.len().min(self.chunk.len() - self.position);
+        buffer[..bytes].copy_from_slice(&self.chunk[self.position..self.position + bytes]);
+        self.position += bytes;
+        Ok(bytes)
+    }
+}
+
+/// Reads `stream` once in chunks of `chunk_size` bytes and passes the whole stream to `count`
+/// calls of `consume`, each on its own thread, returning their results in order. `consume` is
+/// given its index and a reader over the stream.
+///
+/// Returns the first error from reading the stream or from any of the consumers.
+pub fn fan_out<T: Send>(
+    stream: &mut dyn Read,
+    count: usize,
+    chunk_size: usize,
+    consume: impl Fn(usize, &mut dyn Read) -> io::Result<T> + Sync,
+) -> io::Result<Vec<T>> {
+    thread::scope(|scope| {
+        let mut senders = Vec::with_capacity(count);
+        let mut consumers = Vec::with_capacity(count);
+        for index in 0..count {
+            let (sender, receiver) = mpsc::sync_channel(CHANNEL_DEPTH);
+            let consume = &consume;
+            senders.push(sender);
+            consumers.push(scope.spawn(move || {
+                let mut reader = ChunkReader {
+                    receiver,
+                    chunk: Arc::new([]),
+                    position: 0,
+                };
+                consume(index, &mut reader)
+            }));
+        }
+
+        let read_result = loop {
+            let mut chunk = Vec::with_capacity(chunk_size);
+            match stream.take(chunk_size as u64).read_to_end(&mut chunk) {
+                Ok(0) => break Ok(()),
+                Ok(_) => {
+                    let chunk: Arc<[u8]> = chunk.into();
+                    // A consumer only hangs up early if it failed, which its result reports.
+                    for sender in &senders {
+                        let _ = sender.send(Arc::clone(&chunk));
+                    }
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => break Err(e),
+            }
+        };
+        // Ends every consumer's stream, so they finish even if reading failed.
+        drop(senders);
+
+        let results: io::Result<Vec<T>> = consumers
+            .into_iter()
+            .map(|consumer| match consumer.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect();
+        read_result.and(results)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_consumer_reads_the_stream() {
+        let data: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+
+        let copies = fan_out(&mut &data[..], 3, 4096, |index, reader| {
+            let mut copy = Vec::new();
+            reader.read_to_end(&mut copy)?;
+            Ok((index, copy))
+        })
+        .unwrap();
+
+        assert_eq!(copies.len(), 3);
+        for (index, (copy_index, copy)) in copies.into_iter().enumerate() {
+            assert_eq!(copy_index, index);
+            assert!(copy == data);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let failed = fan_out(&mut &b"abc"[..], 2, 1, |index, reader| {
+            io::copy(reader, &mut io::sink())?;
+            if index == 1 {
+                return Err(io::Error::other("consumer failed"));
+            }
+            Ok(())
+        });
+        assert_eq!(failed.unwrap_err().to_string(), "consumer failed");
+
+        struct FailingReader;
+        impl Read for FailingReader {
+            fn read(&mut self, _buffer: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("read failed"))
+            }
+        }
+        let failed = fan_out(&mut FailingReader, 2, 1, |_, reader| {
+            io::copy(reader, &mut io::sink())
+        });
+        assert_eq!(failed.unwrap_err().to_string(), "read failed");
+    }
+}
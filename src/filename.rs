@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use std::ffi::{OsStr, OsString};
+
+/// Converts the bytes of a file name read from a file list or checksum file, returning None if
+/// they can't name a file on this platform.
+#[cfg(unix)]
+pub fn from_bytes(bytes: &[u8]) -> Option<OsString> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(OsStr::from_bytes(bytes).to_os_string())
+}
+
+/// Converts the bytes of a file name read from a file list or checksum file, which must be
+/// valid UTF-8 outside Unix.
+#[cfg(not(unix))]
+pub fn from_bytes(bytes: &[u8]) -> Option<OsString> {
+    str::from_utf8(bytes).ok().map(OsString::from)
+}
+
+/// Escapes a file name for a line of a checksum file as coreutils does, returning None if it
+/// doesn't need escaping. Backslashes, newlines and carriage returns are written as '\\', '\n'
+/// and '\r', and the line holding an escaped name must start with a backslash.
+pub fn escape(name: &[u8]) -> Option<Vec<u8>> {
+    if !name
+        .iter()
+        .any(|byte| matches!(byte, b'\\' | b'\n' | b'\r'))
+    {
+        return None;
+    }
+
+    let mut escaped = Vec::with_capacity(name.len() + 2);
+    for &byte in name {
+        match byte {
+            b'\\' => escaped.extend_from_slice(b"\\\\"),
+            b'\n' => escaped.extend_from_slice(b"\\n"),
+            b'\r' => escaped.extend_from_slice(b"\\r"),
+            _ => escaped.push(byte),
+        }
+    }
+    Some(escaped)
+}
+
+/// Reverses [`escape`], returning None if the name contains any other escape sequence.
+pub fn unescape(name: &[u8]) -> Option<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(name.len());
+    let mut bytes = name.iter();
+
+    while let Some(&byte) = bytes.next() {
+        if byte != b'\\' {
+            unescaped.push(byte);
+            continue;
+        }
+        match bytes.next()? {
+            b'\\' => unescaped.push(b'\\'),
+            b'n' => unescaped.push(b'\n'),
+            b'r' => unescaped.push(b'\r'),
+            _ => return None,
+        }
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape(b"plain name"), None);
+        assert_eq!(
+            escape(b"a\\b\nc\rd").as_deref(),
+            Some(&b"a\\\\b\\nc\\rd"[..])
+        );
+
+        assert_eq!(unescape(b"plain name").as_deref(), Some(&b"plain name"[..]));
+        assert_eq!(
+            unescape(b"a\\\\b\\nc\\rd").as_deref(),
+            Some(&b"a\\b\nc\rd"[..])
+        );
+        assert_eq!(unescape(b"a\\tb"), None);
+        assert_eq!(unescape(b"trailing\\"), None);
+    }
+}
@@ -0,0 +1,203 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Literal(char),
+    /// '?', matching any character except '/'.
+    AnyCharacter,
+    /// '*', matching any run of characters except '/'.
+    AnyRun,
+    /// '**', matching any run of characters including '/'.
+    AnyPath,
+    /// '**/', matching nothing or any run of characters ending in '/'.
+    AnyDirectories,
+    /// '[...]', matching one character in or, if negated, not in the ranges.
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+/// A shell style wildcard pattern for '--include' and '--exclude'.
+///
+/// Supports '?', '*', '[abc]', '[a-z]' and '[!abc]' as in the shell, none of which match '/', and
+/// '**' which matches across directories. A '[' without a closing ']' matches itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+    /// True if the pattern contains a '/' and so is matched against the whole relative path
+    /// rather than the file name.
+    anchored: bool,
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Self {
+        let characters: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+
+        while index < characters.len() {
+            let token = match characters[index] {
+                '?' => Token::AnyCharacter,
+                '*' if characters.get(index + 1) == Some(&'*') => {
+                    index += 1;
+                    if characters.get(index + 1) == Some(&'/') {
+                        index += 1;
+                        Token::AnyDirectories
+                    } else {
+                        Token::AnyPath
+                    }
+                }
+                '*' => Token::AnyRun,
+                '[' => match parse_class(&characters[index + 1..]) {
+                    Some((token, length)) => {
+                        index += length;
+                        token
+                    }
+                    None => Token::Literal('['),
+                },
+                character => Token::Literal(character),
+            };
+            tokens.push(token);
+            index += 1;
+        }
+
+        Self {
+            tokens,
+            anchored: pattern.contains('/'),
+        }
+    }
+
+    /// Returns true if the pattern matches a file named `name` at `relative_path`, a '/'
+    /// separated path relative to the directory being walked.
+    pub fn matches(&self, relative_path: &str, name: &str) -> bool {
+        let text = if self.anchored { relative_path } else { name };
+        let text: Vec<char> = text.chars().collect();
+
+        matches_tokens(&self.tokens, &text)
+    }
+}
+
+/// Parses a character class following its opening '[', returning the class and the number of
+/// characters it used including the closing ']'.
+fn parse_class(characters: &[char]) -> Option<(Token, usize)> {
+    let negated = matches!(characters.first(), Some('!' | '^'));
+    let mut index = usize::from(negated);
+    let mut ranges = Vec::new();
+
+    // A ']' straight after the opening '[' is part of the class rather than closing it.
+    while index < characters.len() && (characters[index] != ']' || ranges.is_empty()) {
+        let start = characters[index];
+        if characters.get(index + 1) == Some(&'-')
+            && characters.get(index + 2).is_some_and(|c| *c != ']')
+        {
+            ranges.push((start, characters[index + 2]));
+            index += 3;
+        } else {
+            ranges.push((start, start));
+            index += 1;
+        }
+    }
+
+    if index >= characters.len() {
+        return None;
+    }
+
+    Some((Token::Class { negated, ranges }, index + 1))
+}
+
+fn matches_tokens(tokens: &[Token], text: &[char]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return text.is_empty();
+    };
+
+    match token {
+        Token::AnyRun => (0..=text.len())
+            .take_while(|&length| length == 0 || text[length - 1] != '/')
+            .any(|length| matches_tokens(rest, &text[length..])),
+        Token::AnyPath => (0..=text.len()).any(|length| matches_tokens(rest, &text[length..])),
+        Token::AnyDirectories => {
+            matches_tokens(rest, text)
+                || (1..=text.len())
+                    .filter(|&length| text[length - 1] == '/')
+                    .any(|length| matches_tokens(rest, &text[length..]))
+        }
+        _ => {
+            let Some((&character, text_rest)) = text.split_first() else {
+                return false;
+            };
+            let matched = match token {
+                Token::Literal(literal) => character == *literal,
+                Token::AnyCharacter => character != '/',
+                Token::Class { negated, ranges } => {
+                    character != '/'
+                        && ranges
+                            .iter()
+                            .any(|(start, end)| (*start..=*end).contains(&character))
+                            != *negated
+                }
+                _ => unreachable!(),
+            };
+
+            matched && matches_tokens(rest, text_rest)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, relative_path: &str) -> bool {
+        let name = relative_path.rsplit('/').next().unwrap();
+        Pattern::new(pattern).matches(relative_path, name)
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(matches("*.txt", "a.txt"));
+        assert!(matches("*.txt", ".txt"));
+        assert!(!matches("*.txt", "a.txt.gz"));
+        assert!(matches("a?c", "abc"));
+        assert!(!matches("a?c", "ac"));
+        assert!(matches("*", "anything"));
+        assert!(matches("a*b*c", "aXXbYYbc"));
+        assert!(!matches("a*b*c", "aXXbYYb"));
+    }
+
+    #[test]
+    fn classes() {
+        assert!(matches("[abc].txt", "b.txt"));
+        assert!(!matches("[abc].txt", "d.txt"));
+        assert!(matches("[a-c]", "c"));
+        assert!(!matches("[!a-c]", "c"));
+        assert!(matches("[^a-c]", "d"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[a-]", "-"));
+        // An unclosed '[' is a literal.
+        assert!(matches("[abc", "[abc"));
+    }
+
+    #[test]
+    fn names_and_paths() {
+        // Patterns without a '/' match the file name at any depth.
+        assert!(matches("*.log", "a/b/c.log"));
+        assert!(!matches("c", "a/b/c.log"));
+        // Patterns with a '/' match the whole relative path, and '*' doesn't cross directories.
+        assert!(matches("a/*/c.log", "a/b/c.log"));
+        assert!(!matches("a/*", "a/b/c.log"));
+        assert!(!matches("b/c.log", "a/b/c.log"));
+    }
+
+    #[test]
+    fn any_directories() {
+        assert!(matches("a/**", "a/b/c.log"));
+        assert!(matches("**/c.log", "c.log"));
+        assert!(matches("**/c.log", "a/b/c.log"));
+        assert!(matches("a/**/c.log", "a/c.log"));
+        assert!(matches("a/**/c.log", "a/b/x/c.log"));
+        assert!(!matches("a/**/c.log", "ab/c.log"));
+        assert!(matches("a/**g", "a/b/c.log"));
+    }
+}
@@ -1,13 +1,313 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
 
-use std::io::Read;
+//! The [`Hash`] and [`Hasher`] traits shared by every algorithm, with one module per family
+//! of algorithms.
 
+use crate::hash_algorithm::digest::Digest;
+use std::io::{ErrorKind, Read};
+
+#[cfg(feature = "blake2")]
+pub mod blake2b;
+#[cfg(feature = "blake2")]
+pub mod blake2s;
+#[cfg(feature = "blake3")]
+pub mod blake3;
+#[cfg(any(feature = "md5", feature = "sha1", feature = "sha2"))]
+mod block_buffer;
+#[cfg(feature = "crc")]
+pub mod crc;
+pub mod digest;
+pub mod hmac;
+#[cfg(feature = "md5")]
 pub mod md5;
+#[cfg(feature = "sha1")]
 pub mod sha1;
+#[cfg(feature = "sha2")]
+pub mod sha256;
+#[cfg(feature = "sha3")]
+pub mod sha3;
+#[cfg(feature = "sha2")]
+pub mod sha512;
+#[cfg(feature = "sum")]
+pub mod sum;
+
+/// Fills `buffer` from `stream`, returning fewer bytes than the buffer holds only at the end of
+/// the stream. Pipes, sockets and some filesystems may legally return short reads mid-stream, so
+/// only a read of zero bytes marks the end, and interrupted reads are retried.
+fn read_block(stream: &mut impl Read, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+
+    while filled < buffer.len() {
+        match stream.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(bytes) => filled += bytes,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(filled)
+}
+
+/// Passes `stream` to `update` in reads of `buffer_size` bytes until the end of the stream.
+fn update_from_stream(
+    mut stream: impl Read,
+    buffer_size: usize,
+    mut update: impl FnMut(&[u8]),
+) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; buffer_size];
+
+    loop {
+        let bytes = read_block(&mut stream, &mut buffer)?;
+        update(&buffer[0..bytes]);
+
+        if bytes < buffer.len() {
+            return Ok(());
+        }
+    }
+}
+
+/// Feeds `stream` into `hasher` until the end of the stream and returns the resulting digest.
+fn hash_stream_into<H: Hasher>(mut hasher: H, stream: impl Read) -> std::io::Result<Digest> {
+    update_from_stream(stream, H::STREAM_BUFFER_SIZE, |data| hasher.update(data))?;
+
+    Ok(hasher.finalize())
+}
 
+/// A hash algorithm, implemented by a unit struct such as [`md5::MD5`] whose hasher does the
+/// work.
 pub trait Hash {
-    fn hash_slice(message: &[u8]) -> String;
+    /// Incremental hasher used to compute digests for this algorithm.
+    type Hasher: Hasher;
+
+    /// Hashes `message` into a digest of the default length.
+    fn hash_slice(message: &[u8]) -> Digest {
+        let mut hasher = Self::Hasher::new();
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    /// Hashes `stream` until its end into a digest of the default length.
+    fn hash_stream(stream: impl Read) -> std::io::Result<Digest> {
+        hash_stream_into(Self::Hasher::new(), stream)
+    }
+
+    /// Hashes `message` into a digest of `output_bits` bits for algorithms with a variable
+    /// output length. Panics if the length is unsupported by the algorithm.
+    fn hash_slice_with_output_bits(message: &[u8], output_bits: usize) -> Digest
+    where
+        Self::Hasher: VariableOutputHasher,
+    {
+        let mut hasher = Self::Hasher::with_output_bits(output_bits);
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    /// Hashes `stream` into a digest of `output_bits` bits for algorithms with a variable
+    /// output length. Panics if the length is unsupported by the algorithm.
+    fn hash_stream_with_output_bits(
+        stream: impl Read,
+        output_bits: usize,
+    ) -> std::io::Result<Digest>
+    where
+        Self::Hasher: VariableOutputHasher,
+    {
+        hash_stream_into(Self::Hasher::with_output_bits(output_bits), stream)
+    }
+}
+
+/// Marker for the cryptographic hash algorithms, which unlike checksums such as the CRCs can
+/// have an [`hmac::Hmac`] built on them.
+pub trait CryptographicHash: Hash {}
+
+/// Streaming interface for computing a digest over data supplied in pieces of any length.
+pub trait Hasher {
+    /// Size in bytes of the blocks processed by the compression function.
+    const BLOCK_SIZE: usize;
+
+    /// Size in bytes of the reads used when hashing a stream. Hashers that split large updates
+    /// across threads raise this so each update carries enough data to divide.
+    const STREAM_BUFFER_SIZE: usize = Self::BLOCK_SIZE;
+
+    /// Creates a hasher with no data processed.
+    fn new() -> Self;
+
+    /// Processes `data`, buffering any partial block until more data or `finalize` arrives.
+    fn update(&mut self, data: &[u8]);
+
+    /// Pads the buffered data and returns the digest of everything passed to `update`.
+    fn finalize(self) -> Digest;
+
+    /// Discards all processed data, returning the hasher to the state produced by `new`.
+    fn reset(&mut self);
+
+    /// Processes `stream` until its end and returns the digest of everything passed to the
+    /// hasher, for hashers configured beyond what [`Hash`] offers.
+    fn finalize_stream(self, stream: impl Read) -> std::io::Result<Digest>
+    where
+        Self: Sized,
+    {
+        hash_stream_into(self, stream)
+    }
+}
+
+/// Hashers able to produce digests of a caller chosen length, such as extendable-output
+/// functions.
+pub trait VariableOutputHasher: Hasher {
+    /// Shortest supported digest length in bits.
+    const MIN_OUTPUT_BITS: usize;
+
+    /// Longest supported digest length in bits.
+    const MAX_OUTPUT_BITS: usize;
+
+    /// Digest length in bits produced by a hasher created with `new`.
+    const DEFAULT_OUTPUT_BITS: usize;
+
+    /// Creates a hasher producing digests of `output_bits` bits. Panics unless `output_bits` is a
+    /// multiple of 8 between `MIN_OUTPUT_BITS` and `MAX_OUTPUT_BITS`.
+    fn with_output_bits(output_bits: usize) -> Self;
+
+    /// Returns true if `output_bits` is a digest length this hasher can produce.
+    fn supports_output_bits(output_bits: usize) -> bool {
+        output_bits.is_multiple_of(8)
+            && (Self::MIN_OUTPUT_BITS..=Self::MAX_OUTPUT_BITS).contains(&output_bits)
+    }
+
+    #[doc(hidden)]
+    fn assert_output_bits(output_bits: usize) {
+        assert!(
+            Self::supports_output_bits(output_bits),
+            "Unsupported digest length of {output_bits} bits."
+        );
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "md5",
+        feature = "sha1",
+        feature = "sha2",
+        feature = "sha3",
+        feature = "blake2",
+        feature = "blake3",
+        feature = "crc",
+        feature = "sum"
+    )
+))]
+mod tests {
+    use super::*;
+    #[cfg(feature = "blake3")]
+    use crate::hash_algorithm::blake3::BLAKE3;
+    #[cfg(feature = "crc")]
+    use crate::hash_algorithm::crc::{Crc, Crc32, Crc32c, Crc64};
+    #[cfg(feature = "md5")]
+    use crate::hash_algorithm::md5::MD5;
+    #[cfg(feature = "sha1")]
+    use crate::hash_algorithm::sha1::SHA1;
+    #[cfg(feature = "sha3")]
+    use crate::hash_algorithm::sha3::{SHA3_224, SHA3_256, SHA3_384, SHA3_512, SHAKE128, SHAKE256};
+    #[cfg(feature = "sum")]
+    use crate::hash_algorithm::sum::{Bsd, Sysv};
+    #[cfg(feature = "blake2")]
+    use crate::hash_algorithm::{blake2b::BLAKE2b, blake2s::BLAKE2s};
+    #[cfg(feature = "sha2")]
+    use crate::hash_algorithm::{
+        sha256::{SHA224, SHA256},
+        sha512::{SHA384, SHA512, SHA512_224, SHA512_256},
+    };
+    use std::io::{self, Cursor};
+
+    /// Reader returning between 1 and 63 bytes per call, with an interrupted read in between.
+    struct ChunkyReader {
+        data: Cursor<Vec<u8>>,
+        reads: usize,
+    }
+
+    impl Read for ChunkyReader {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+
+            if self.reads.is_multiple_of(5) {
+                return Err(io::Error::from(ErrorKind::Interrupted));
+            }
+
+            let limit = (self.reads * 7 % 63 + 1).min(buffer.len());
+            self.data.read(&mut buffer[0..limit])
+        }
+    }
+
+    fn assert_chunky_stream<H: Hash>() {
+        for length in [
+            0, 1, 55, 56, 63, 64, 65, 111, 112, 127, 128, 129, 1000, 10_000,
+        ] {
+            let message: Vec<u8> = (0..length).map(|i| (i * 31) as u8).collect();
+            let reader = ChunkyReader {
+                data: Cursor::new(message.clone()),
+                reads: 0,
+            };
+
+            assert_eq!(H::hash_stream(reader).unwrap(), H::hash_slice(&message));
+        }
+    }
+
+    #[test]
+    fn chunky_stream() {
+        #[cfg(feature = "md5")]
+        assert_chunky_stream::<MD5>();
+        #[cfg(feature = "sha1")]
+        assert_chunky_stream::<SHA1>();
+        #[cfg(feature = "sha2")]
+        {
+            assert_chunky_stream::<SHA224>();
+            assert_chunky_stream::<SHA256>();
+            assert_chunky_stream::<SHA384>();
+            assert_chunky_stream::<SHA512>();
+            assert_chunky_stream::<SHA512_224>();
+            assert_chunky_stream::<SHA512_256>();
+        }
+        #[cfg(feature = "sha3")]
+        {
+            assert_chunky_stream::<SHA3_224>();
+            assert_chunky_stream::<SHA3_256>();
+            assert_chunky_stream::<SHA3_384>();
+            assert_chunky_stream::<SHA3_512>();
+            assert_chunky_stream::<SHAKE128>();
+            assert_chunky_stream::<SHAKE256>();
+        }
+        #[cfg(feature = "blake2")]
+        {
+            assert_chunky_stream::<BLAKE2b>();
+            assert_chunky_stream::<BLAKE2s>();
+        }
+        #[cfg(feature = "blake3")]
+        assert_chunky_stream::<BLAKE3>();
+        #[cfg(feature = "crc")]
+        {
+            assert_chunky_stream::<Crc>();
+            assert_chunky_stream::<Crc32>();
+            assert_chunky_stream::<Crc32c>();
+            assert_chunky_stream::<Crc64>();
+        }
+        #[cfg(feature = "sum")]
+        {
+            assert_chunky_stream::<Bsd>();
+            assert_chunky_stream::<Sysv>();
+        }
+    }
+
+    #[cfg(feature = "md5")]
+    #[test]
+    fn stream_error() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _buffer: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("read failed"))
+            }
+        }
 
-    fn hash_stream(message: impl Read) -> std::io::Result<String>;
+        assert!(MD5::hash_stream(FailingReader).is_err());
+    }
 }
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! BLAKE2b as defined in rfc 7693, with digests of 8 to 512 bits.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, VariableOutputHasher, digest::Digest,
+};
+
+// Initialisation vector shared with SHA-512 as defined in rfc 7693 section 2.6.
+const IV: [u64; 8] = [
+    0x6A_09_E6_67_F3_BC_C9_08_u64,
+    0xBB_67_AE_85_84_CA_A7_3B_u64,
+    0x3C_6E_F3_72_FE_94_F8_2B_u64,
+    0xA5_4F_F5_3A_5F_1D_36_F1_u64,
+    0x51_0E_52_7F_AD_E6_82_D1_u64,
+    0x9B_05_68_8C_2B_3E_6C_1F_u64,
+    0x1F_83_D9_AB_FB_41_BD_6B_u64,
+    0x5B_E0_CD_19_13_7E_21_79_u64,
+];
+
+// Message word permutations for each round as defined in rfc 7693 section 2.7. BLAKE2b runs 12
+// rounds, reusing the first two permutations for the final two rounds.
+const SIGMA: [[usize; 16]; 12] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+];
+
+/// BLAKE2b producing 512 bit digests by default.
+pub struct BLAKE2b {}
+
+/// Incremental BLAKE2b hasher accepting data in pieces of any length and producing digests of a
+/// chosen length.
+#[derive(Clone)]
+pub struct BLAKE2bHasher {
+    state: [u64; 8],
+    // The final block must be compressed with the last block flag set, so a full block is only
+    // compressed once more data arrives.
+    buffer: [u8; 128],
+    buffer_length: usize,
+    total_data_size_bytes: u128,
+    output_bits: usize,
+}
+
+/// The G mixing function as defined in rfc 7693 section 3.1.
+fn mix(v: &mut [u64; 16], a: usize, b: usize, c: usize, d: usize, x: u64, y: u64) {
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = v[c].wrapping_add(v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The compression function F as defined in rfc 7693 section 3.2. `counter` is the number of
+/// bytes processed including this block.
+fn compress(state: &mut [u64; 8], block: &[u8; 128], counter: u128, last_block: bool) {
+    let mut message = [0u64; 16];
+    for (word, bytes) in message.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    let mut v = [0u64; 16];
+    v[0..8].copy_from_slice(state);
+    v[8..16].copy_from_slice(&IV);
+    v[12] ^= counter as u64;
+    v[13] ^= (counter >> 64) as u64;
+    if last_block {
+        v[14] = !v[14];
+    }
+
+    for sigma in SIGMA {
+        mix(&mut v, 0, 4, 8, 12, message[sigma[0]], message[sigma[1]]);
+        mix(&mut v, 1, 5, 9, 13, message[sigma[2]], message[sigma[3]]);
+        mix(&mut v, 2, 6, 10, 14, message[sigma[4]], message[sigma[5]]);
+        mix(&mut v, 3, 7, 11, 15, message[sigma[6]], message[sigma[7]]);
+        mix(&mut v, 0, 5, 10, 15, message[sigma[8]], message[sigma[9]]);
+        mix(&mut v, 1, 6, 11, 12, message[sigma[10]], message[sigma[11]]);
+        mix(&mut v, 2, 7, 8, 13, message[sigma[12]], message[sigma[13]]);
+        mix(&mut v, 3, 4, 9, 14, message[sigma[14]], message[sigma[15]]);
+    }
+
+    for i in 0..8 {
+        state[i] ^= v[i] ^ v[i + 8];
+    }
+}
+
+impl Hasher for BLAKE2bHasher {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new() -> Self {
+        Self::with_output_bits(Self::DEFAULT_OUTPUT_BITS)
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.buffer_length == Self::BLOCK_SIZE {
+                self.total_data_size_bytes += Self::BLOCK_SIZE as u128;
+                compress(
+                    &mut self.state,
+                    &self.buffer,
+                    self.total_data_size_bytes,
+                    false,
+                );
+                self.buffer_length = 0;
+            }
+
+            let bytes = (Self::BLOCK_SIZE - self.buffer_length).min(data.len());
+            self.buffer[self.buffer_length..self.buffer_length + bytes]
+                .copy_from_slice(&data[0..bytes]);
+            self.buffer_length += bytes;
+            data = &data[bytes..];
+        }
+    }
+
+    fn finalize(mut self) -> Digest {
+        self.total_data_size_bytes += self.buffer_length as u128;
+        self.buffer[self.buffer_length..].fill(0);
+        compress(
+            &mut self.state,
+            &self.buffer,
+            self.total_data_size_bytes,
+            true,
+        );
+
+        let message_digest: Vec<u8> = self
+            .state
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(self.output_bits / 8)
+            .collect();
+
+        Digest::from(message_digest)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::with_output_bits(self.output_bits);
+    }
+}
+
+impl VariableOutputHasher for BLAKE2bHasher {
+    const MIN_OUTPUT_BITS: usize = 8;
+    const MAX_OUTPUT_BITS: usize = 512;
+    const DEFAULT_OUTPUT_BITS: usize = 512;
+
+    fn with_output_bits(output_bits: usize) -> Self {
+        Self::assert_output_bits(output_bits);
+
+        // Parameter block with the digest length, no key, and a fanout and depth of 1.
+        let mut state = IV;
+        state[0] ^= 0x01_01_00_00_u64 ^ (output_bits / 8) as u64;
+
+        Self {
+            state,
+            buffer: [0u8; 128],
+            buffer_length: 0,
+            total_data_size_bytes: 0,
+            output_bits,
+        }
+    }
+}
+
+impl Hash for BLAKE2b {
+    type Hasher = BLAKE2bHasher;
+}
+
+impl CryptographicHash for BLAKE2b {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_hash() {
+        let test_vec = Vec::new();
+
+        assert_eq!(
+            BLAKE2b::hash_slice(&test_vec).to_hex(),
+            "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce"
+        );
+    }
+
+    #[test]
+    fn rfc_hash_suite() {
+        let mut test_vec = Vec::new();
+        test_vec.extend_from_slice("abc".as_bytes());
+
+        // Example from rfc 7693 appendix A.
+        assert_eq!(
+            BLAKE2b::hash_slice(&test_vec).to_hex(),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+        assert_eq!(
+            BLAKE2b::hash_slice_with_output_bits(&test_vec, 256).to_hex(),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+        assert_eq!(
+            BLAKE2b::hash_slice_with_output_bits(&test_vec, 8).to_hex(),
+            "6b"
+        );
+
+        test_vec.clear();
+        test_vec.extend_from_slice(
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".as_bytes(),
+        );
+
+        assert_eq!(
+            BLAKE2b::hash_slice(&test_vec).to_hex(),
+            "7285ff3e8bd768d69be62b3bf18765a325917fa9744ac2f582a20850bc2b1141ed1b3e4528595acc90772bdf2d37dc8a47130b44f33a02e8730e5ad8e166e888"
+        );
+
+        // Exact multiple of the block size, so the last full block carries the final flag.
+        test_vec.clear();
+        for _ in 0..4 {
+            test_vec.extend(0..=255_u8);
+        }
+
+        assert_eq!(
+            BLAKE2b::hash_slice(&test_vec).to_hex(),
+            "6b490f42e902f61b1ee12d3c85e34152e37c94d07ab9ea577cad6a6eb4690fad38064f53a19c225703a5c52cdc9a85add71b339d327e1630ee3432b920240e8a"
+        );
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = BLAKE2b::hash_slice(&test_vec);
+
+        // Split the message at every chunk size up to and past the block size.
+        for chunk_size in 1..=300 {
+            let mut hasher = BLAKE2bHasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = BLAKE2bHasher::with_output_bits(256);
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(
+            hasher.finalize().to_hex(),
+            "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"
+        );
+    }
+}
@@ -0,0 +1,669 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! BLAKE3 in its hash, keyed hash and key derivation modes, with extendable output.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, VariableOutputHasher, digest::Digest, hash_stream_into,
+};
+use std::{io::Read, thread};
+
+// Initialisation vector shared with SHA-256 as defined in the BLAKE3 specification section 2.2.
+const IV: [u32; 8] = [
+    0x6A_09_E6_67_u32,
+    0xBB_67_AE_85_u32,
+    0x3C_6E_F3_72_u32,
+    0xA5_4F_F5_3A_u32,
+    0x51_0E_52_7F_u32,
+    0x9B_05_68_8C_u32,
+    0x1F_83_D9_AB_u32,
+    0x5B_E0_CD_19_u32,
+];
+
+// Permutation applied to the message words between rounds as defined in section 2.2.
+const MESSAGE_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+// Domain separation flags as defined in section 2.1.
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+const KEYED_HASH: u32 = 1 << 4;
+const DERIVE_KEY_CONTEXT: u32 = 1 << 5;
+const DERIVE_KEY_MATERIAL: u32 = 1 << 6;
+
+const BLOCK_LENGTH: usize = 64;
+const CHUNK_LENGTH: usize = 1024;
+
+// Longest digest hashsum will read from the extendable output.
+const BLAKE3_MAX_OUTPUT_BITS: usize = 65536;
+
+// Subtrees smaller than this are hashed on the calling thread, as spawning a thread would cost
+// more than it saves.
+const PARALLEL_MIN_BYTES: usize = 128 * CHUNK_LENGTH;
+
+/// BLAKE3 producing 256 bit digests by default.
+pub struct BLAKE3 {}
+
+/// Incremental BLAKE3 hasher accepting data in pieces of any length and producing digests of a
+/// chosen length. Large updates are split into subtrees hashed across one thread per CPU, or as
+/// many as set with [`with_threads`](Self::with_threads).
+#[derive(Clone)]
+pub struct BLAKE3Hasher {
+    key: [u32; 8],
+    flags: u32,
+    chunk: ChunkState,
+    // Chaining values of completed subtrees, holding one per set bit of the number of chunks
+    // hashed so far with the largest subtree first.
+    cv_stack: Vec<[u32; 8]>,
+    output_bits: usize,
+    // Most threads hashing an update at once, or None for one per CPU.
+    threads: Option<usize>,
+}
+
+/// State of the chunk currently being hashed.
+#[derive(Clone)]
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LENGTH],
+    block_length: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+/// Inputs to the final compression of a node. The root node is compressed again with an
+/// incrementing counter for every 64 bytes of output.
+struct Output {
+    chaining_value: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_length: u32,
+    flags: u32,
+}
+
+/// The G mixing function as defined in section 2.2.
+fn mix(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, x: u32, y: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// The compression function as defined in section 2.2, returning the full 16 word state so the
+/// root node can produce extended output.
+fn compress(
+    chaining_value: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_length: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [
+        chaining_value[0],
+        chaining_value[1],
+        chaining_value[2],
+        chaining_value[3],
+        chaining_value[4],
+        chaining_value[5],
+        chaining_value[6],
+        chaining_value[7],
+        IV[0],
+        IV[1],
+        IV[2],
+        IV[3],
+        counter as u32,
+        (counter >> 32) as u32,
+        block_length,
+        flags,
+    ];
+    let mut message = *block_words;
+
+    for round in 0..7 {
+        mix(&mut state, 0, 4, 8, 12, message[0], message[1]);
+        mix(&mut state, 1, 5, 9, 13, message[2], message[3]);
+        mix(&mut state, 2, 6, 10, 14, message[4], message[5]);
+        mix(&mut state, 3, 7, 11, 15, message[6], message[7]);
+        mix(&mut state, 0, 5, 10, 15, message[8], message[9]);
+        mix(&mut state, 1, 6, 11, 12, message[10], message[11]);
+        mix(&mut state, 2, 7, 8, 13, message[12], message[13]);
+        mix(&mut state, 3, 4, 9, 14, message[14], message[15]);
+
+        if round < 6 {
+            message = MESSAGE_PERMUTATION.map(|i| message[i]);
+        }
+    }
+
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+
+    state
+}
+
+fn words_from_le_bytes<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut words = [0u32; N];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    words
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        let state = compress(
+            &self.chaining_value,
+            &self.block_words,
+            self.counter,
+            self.block_length,
+            self.flags,
+        );
+
+        state[0..8].try_into().unwrap()
+    }
+
+    fn root_output_bytes(&self, output_bytes: usize) -> Vec<u8> {
+        let mut output = Vec::with_capacity(output_bytes);
+        let mut counter = 0;
+
+        while output.len() < output_bytes {
+            let state = compress(
+                &self.chaining_value,
+                &self.block_words,
+                counter,
+                self.block_length,
+                self.flags | ROOT,
+            );
+            let bytes = state.iter().flat_map(|word| word.to_le_bytes());
+            output.extend(bytes.take(output_bytes - output.len()));
+            counter += 1;
+        }
+
+        output
+    }
+}
+
+impl ChunkState {
+    fn new(key: &[u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        Self {
+            chaining_value: *key,
+            chunk_counter,
+            block: [0u8; BLOCK_LENGTH],
+            block_length: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLOCK_LENGTH * self.blocks_compressed + self.block_length
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    /// Processes `data`, which must fit in the remainder of the chunk. The last block is kept
+    /// buffered as it has to be compressed with the chunk end flag.
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            if self.block_length == BLOCK_LENGTH {
+                let state = compress(
+                    &self.chaining_value,
+                    &words_from_le_bytes(&self.block),
+                    self.chunk_counter,
+                    BLOCK_LENGTH as u32,
+                    self.flags | self.start_flag(),
+                );
+                self.chaining_value = state[0..8].try_into().unwrap();
+                self.blocks_compressed += 1;
+                self.block = [0u8; BLOCK_LENGTH];
+                self.block_length = 0;
+            }
+
+            let bytes = (BLOCK_LENGTH - self.block_length).min(data.len());
+            self.block[self.block_length..self.block_length + bytes]
+                .copy_from_slice(&data[0..bytes]);
+            self.block_length += bytes;
+            data = &data[bytes..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            chaining_value: self.chaining_value,
+            block_words: words_from_le_bytes(&self.block),
+            counter: self.chunk_counter,
+            block_length: self.block_length as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8], key: &[u32; 8], flags: u32) -> Output {
+    let mut block_words = [0u32; 16];
+    block_words[0..8].copy_from_slice(left);
+    block_words[8..16].copy_from_slice(right);
+
+    Output {
+        chaining_value: *key,
+        block_words,
+        counter: 0,
+        block_length: BLOCK_LENGTH as u32,
+        flags: flags | PARENT,
+    }
+}
+
+/// Returns the chaining value of a complete, non root subtree over `input`, which must be a
+/// power of two number of chunks starting at chunk `chunk_counter`. The two halves of each
+/// subtree are independent, so they are split across up to `threads` threads.
+fn subtree_chaining_value(
+    input: &[u8],
+    key: &[u32; 8],
+    chunk_counter: u64,
+    flags: u32,
+    threads: usize,
+) -> [u32; 8] {
+    if input.len() == CHUNK_LENGTH {
+        let mut chunk = ChunkState::new(key, chunk_counter, flags);
+        chunk.update(input);
+        return chunk.output().chaining_value();
+    }
+
+    let (left, right) = input.split_at(input.len() / 2);
+    let right_counter = chunk_counter + (left.len() / CHUNK_LENGTH) as u64;
+
+    let (left_cv, right_cv) = if threads > 1 && input.len() >= PARALLEL_MIN_BYTES {
+        thread::scope(|scope| {
+            let right_thread = scope
+                .spawn(|| subtree_chaining_value(right, key, right_counter, flags, threads / 2));
+            let left_cv =
+                subtree_chaining_value(left, key, chunk_counter, flags, threads - threads / 2);
+
+            (left_cv, right_thread.join().unwrap())
+        })
+    } else {
+        (
+            subtree_chaining_value(left, key, chunk_counter, flags, 1),
+            subtree_chaining_value(right, key, right_counter, flags, 1),
+        )
+    };
+
+    parent_output(&left_cv, &right_cv, key, flags).chaining_value()
+}
+
+impl BLAKE3Hasher {
+    fn new_internal(key: [u32; 8], flags: u32, output_bits: usize) -> Self {
+        Self::assert_output_bits(output_bits);
+
+        Self {
+            key,
+            flags,
+            chunk: ChunkState::new(&key, 0, flags),
+            cv_stack: Vec::new(),
+            output_bits,
+            threads: None,
+        }
+    }
+
+    /// Limits the threads hashing each large update to `threads`, so callers already hashing on
+    /// several threads don't oversubscribe the CPUs. A limit of one hashes on the calling thread.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads.max(1));
+        self
+    }
+
+    /// Creates a hasher in keyed hash mode producing digests of `output_bits` bits.
+    pub fn new_keyed(key: &[u8; 32], output_bits: usize) -> Self {
+        Self::new_internal(words_from_le_bytes(key), KEYED_HASH, output_bits)
+    }
+
+    /// Creates a hasher in key derivation mode producing keys of `output_bits` bits. The context
+    /// string should be hardcoded, globally unique and application specific.
+    pub fn new_derive_key(context: &str, output_bits: usize) -> Self {
+        let mut context_hasher = Self::new_internal(IV, DERIVE_KEY_CONTEXT, 256);
+        context_hasher.update(context.as_bytes());
+        let context_key = context_hasher.finalize();
+
+        Self::new_internal(
+            words_from_le_bytes(context_key.as_bytes()),
+            DERIVE_KEY_MATERIAL,
+            output_bits,
+        )
+    }
+
+    /// Adds the chaining value of a subtree ending after `total_chunks` chunks, merging
+    /// completed subtrees of equal size into their parents.
+    fn push_chaining_value(&mut self, chaining_value: [u32; 8], total_chunks: u64) {
+        self.cv_stack.push(chaining_value);
+
+        while self.cv_stack.len() > total_chunks.count_ones() as usize {
+            let right = self.cv_stack.pop().unwrap();
+            let left = self.cv_stack.pop().unwrap();
+            self.cv_stack
+                .push(parent_output(&left, &right, &self.key, self.flags).chaining_value());
+        }
+    }
+}
+
+impl Hasher for BLAKE3Hasher {
+    const BLOCK_SIZE: usize = BLOCK_LENGTH;
+    // Large enough for each update to be split into subtrees for every thread.
+    const STREAM_BUFFER_SIZE: usize = 8192 * CHUNK_LENGTH;
+
+    fn new() -> Self {
+        Self::with_output_bits(Self::DEFAULT_OUTPUT_BITS)
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // A full chunk is only finished once more data arrives, as the last chunk must be
+            // compressed with the root flag if it is the only one.
+            if self.chunk.len() == CHUNK_LENGTH {
+                let chaining_value = self.chunk.output().chaining_value();
+                let total_chunks = self.chunk.chunk_counter + 1;
+                self.push_chaining_value(chaining_value, total_chunks);
+                self.chunk = ChunkState::new(&self.key, total_chunks, self.flags);
+            }
+
+            // At a chunk boundary hash the largest complete subtree that keeps the tree aligned
+            // in one step, holding back at least one byte for the final chunk.
+            if self.chunk.len() == 0 && data.len() > CHUNK_LENGTH {
+                let chunk_counter = self.chunk.chunk_counter;
+                let mut subtree_chunks = 1 << ((data.len() - 1) / CHUNK_LENGTH).ilog2();
+                while !chunk_counter.is_multiple_of(subtree_chunks as u64) {
+                    subtree_chunks /= 2;
+                }
+
+                let subtree_bytes = subtree_chunks * CHUNK_LENGTH;
+                let threads = match self.threads {
+                    _ if subtree_bytes < PARALLEL_MIN_BYTES => 1,
+                    Some(threads) => threads,
+                    None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+                };
+                let chaining_value = subtree_chaining_value(
+                    &data[0..subtree_bytes],
+                    &self.key,
+                    chunk_counter,
+                    self.flags,
+                    threads,
+                );
+
+                let total_chunks = chunk_counter + subtree_chunks as u64;
+                self.push_chaining_value(chaining_value, total_chunks);
+                self.chunk = ChunkState::new(&self.key, total_chunks, self.flags);
+                data = &data[subtree_bytes..];
+                continue;
+            }
+
+            let bytes = (CHUNK_LENGTH - self.chunk.len()).min(data.len());
+            self.chunk.update(&data[0..bytes]);
+            data = &data[bytes..];
+        }
+    }
+
+    fn finalize(self) -> Digest {
+        let mut output = self.chunk.output();
+        for chaining_value in self.cv_stack.iter().rev() {
+            output = parent_output(
+                chaining_value,
+                &output.chaining_value(),
+                &self.key,
+                self.flags,
+            );
+        }
+
+        Digest::from(output.root_output_bytes(self.output_bits / 8))
+    }
+
+    fn reset(&mut self) {
+        let threads = self.threads;
+        *self = Self::new_internal(self.key, self.flags, self.output_bits);
+        self.threads = threads;
+    }
+}
+
+impl VariableOutputHasher for BLAKE3Hasher {
+    const MIN_OUTPUT_BITS: usize = 8;
+    const MAX_OUTPUT_BITS: usize = BLAKE3_MAX_OUTPUT_BITS;
+    const DEFAULT_OUTPUT_BITS: usize = 256;
+
+    fn with_output_bits(output_bits: usize) -> Self {
+        Self::new_internal(IV, 0, output_bits)
+    }
+}
+
+impl Hash for BLAKE3 {
+    type Hasher = BLAKE3Hasher;
+}
+
+impl CryptographicHash for BLAKE3 {}
+
+impl BLAKE3 {
+    /// Hashes `stream` in keyed hash mode into a digest of `output_bits` bits.
+    pub fn hash_stream_keyed(
+        stream: impl Read,
+        key: &[u8; 32],
+        output_bits: usize,
+    ) -> std::io::Result<Digest> {
+        hash_stream_into(BLAKE3Hasher::new_keyed(key, output_bits), stream)
+    }
+
+    /// Derives a key of `output_bits` bits from the key material in `stream` and `context`.
+    pub fn hash_stream_derive_key(
+        stream: impl Read,
+        context: &str,
+        output_bits: usize,
+    ) -> std::io::Result<Digest> {
+        hash_stream_into(BLAKE3Hasher::new_derive_key(context, output_bits), stream)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Key and context string used by the official BLAKE3 test vectors.
+    const TEST_KEY: &[u8; 32] = b"whats the Elvish word for friend";
+    const TEST_CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+    // (input length, hash, keyed hash, derived key) from the official BLAKE3 test vectors, where
+    // the input is the repeating byte sequence 0, 1, ..., 250.
+    const TEST_VECTORS: [(usize, &str, &str, &str); 14] = [
+        (
+            0,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26",
+            "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d",
+        ),
+        (
+            1,
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213",
+            "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b",
+            "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c",
+        ),
+        (
+            1023,
+            "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11",
+            "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e",
+            "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea5",
+        ),
+        (
+            1024,
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7",
+            "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4",
+            "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a689684302706",
+        ),
+        (
+            1025,
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444",
+            "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69",
+            "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb",
+        ),
+        (
+            2048,
+            "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a",
+            "879cf1fa2ea0e79126cb1063617a05b6ad9d0b696d0d757cf053439f60a99dd1",
+            "7b2945cb4fef70885cc5d78a87bf6f6207dd901ff239201351ffac04e1088a23",
+        ),
+        (
+            2049,
+            "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030",
+            "9f29700902f7c86e514ddc4df1e3049f258b2472b6dd5267f61bf13983b78dd5",
+            "2ea477c5515cc3dd606512ee72bb3e0e758cfae7232826f35fb98ca1bcbdf273",
+        ),
+        (
+            3072,
+            "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2",
+            "044a0e7b172a312dc02a4c9a818c036ffa2776368d7f528268d2e6b5df191770",
+            "050df97f8c2ead654d9bb3ab8c9178edcd902a32f8495949feadcc1e0480c46b",
+        ),
+        (
+            3073,
+            "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3",
+            "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a",
+            "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081",
+        ),
+        (
+            4096,
+            "015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969",
+            "befc660aea2f1718884cd8deb9902811d332f4fc4a38cf7c7300d597a081bfc0",
+            "1e0d7f3db8c414c97c6307cbda6cd27ac3b030949da8e23be1a1a924ad2f25b9",
+        ),
+        (
+            4097,
+            "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995",
+            "00df940cd36bb9fa7cbbc3556744e0dbc8191401afe70520ba292ee3ca80abbc",
+            "aca51029626b55fda7117b42a7c211f8c6e9ba4fe5b7a8ca922f34299500ead8",
+        ),
+        (
+            8193,
+            "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b",
+            "954a2a75420c8d6547e3ba5b98d963e6fa6491addc8c023189cc519821b4a1f5",
+            "af1e0346e389b17c23200270a64aa4e1ead98c61695d917de7d5b00491c9b0f1",
+        ),
+        (
+            31744,
+            "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47",
+            "efa53b389ab67c593dba624d898d0f7353ab99e4ac9d42302ee64cbf9939a419",
+            "39772aef80e0ebe60596361e45b061e8f417429d529171b6764468c22928e28e",
+        ),
+        (
+            102400,
+            "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085",
+            "1c35d1a5811083fd7119f5d5d1ba027b4d01c0c6c49fb6ff2cf75393ea5db4a7",
+            "4652cff7a3f385a6103b5c260fc1593e13c778dbe608efb092fe7ee69df6e9c6",
+        ),
+    ];
+
+    fn test_input(length: usize) -> Vec<u8> {
+        (0..length).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn keyed_hash(message: &[u8], output_bits: usize) -> Digest {
+        let mut hasher = BLAKE3Hasher::new_keyed(TEST_KEY, output_bits);
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    fn derive_key(message: &[u8], output_bits: usize) -> Digest {
+        let mut hasher = BLAKE3Hasher::new_derive_key(TEST_CONTEXT, output_bits);
+        hasher.update(message);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn empty_hash() {
+        let test_vec = Vec::new();
+
+        assert_eq!(
+            BLAKE3::hash_slice(&test_vec).to_hex(),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn official_hash_suite() {
+        for (length, hash, keyed, derived) in TEST_VECTORS {
+            let test_vec = test_input(length);
+
+            assert_eq!(BLAKE3::hash_slice(&test_vec).to_hex(), hash);
+            assert_eq!(keyed_hash(&test_vec, 256).to_hex(), keyed);
+            assert_eq!(derive_key(&test_vec, 256).to_hex(), derived);
+        }
+    }
+
+    #[test]
+    fn extended_output() {
+        let test_vec = "abc".as_bytes();
+
+        // Longer than one compression output so the root node is compressed more than once.
+        assert_eq!(
+            BLAKE3::hash_slice_with_output_bits(test_vec, 131 * 8).to_hex(),
+            "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d851fb250ae7393f5d02813b65d521a0d492d9ba09cf7ce7f4cffd900f23374bf0bc08a1fb0b38ed276181ccbd9f7b7edbddf9f86404ad7929605f6ffa3fb1ac87983105f013384f2f11d38879c985d47003804b905f0c38975e28d36804bb60d8c303653"
+        );
+        assert_eq!(
+            BLAKE3::hash_slice_with_output_bits(test_vec, 8).to_hex(),
+            "64"
+        );
+    }
+
+    #[test]
+    fn parallel_hash() {
+        // Large enough for the subtree to be split across threads.
+        let test_vec = test_input(1024 * 1024 + 1);
+        let expected = "2f053cd7472cf0cd2f9adaf45c1180255b91b9a865404a63671a0ee5f792ed33";
+
+        assert_eq!(BLAKE3::hash_slice(&test_vec).to_hex(), expected);
+        assert_eq!(
+            subtree_chaining_value(&test_vec[0..1024 * 1024], &IV, 0, 0, 4),
+            subtree_chaining_value(&test_vec[0..1024 * 1024], &IV, 0, 0, 1)
+        );
+
+        // Starting part way into the input leaves the subtrees unaligned with the chunk counter.
+        let mut hasher = BLAKE3Hasher::new();
+        hasher.update(&test_vec[0..3 * 1024 + 17]);
+        hasher.update(&test_vec[3 * 1024 + 17..]);
+
+        assert_eq!(hasher.finalize().to_hex(), expected);
+
+        for threads in [0, 1, 3] {
+            let mut hasher = BLAKE3Hasher::new().with_threads(threads);
+            hasher.update(&test_vec);
+            assert_eq!(hasher.finalize().to_hex(), expected);
+        }
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec = test_input(10_000);
+        let expected = BLAKE3::hash_slice(&test_vec);
+
+        // Split the message at chunk sizes around the block and chunk lengths.
+        for chunk_size in (1..=130).chain(1020..=1030).chain([2048, 4095, 4097]) {
+            let mut hasher = BLAKE3Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = BLAKE3Hasher::new_derive_key(TEST_CONTEXT, 512);
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(hasher.finalize(), derive_key("abc".as_bytes(), 512));
+    }
+}
@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+/// Buffers input data until a full block of `N` bytes is available for a compression function.
+#[derive(Clone)]
+pub(crate) struct BlockBuffer<const N: usize> {
+    buffer: [u8; N],
+    length: usize,
+}
+
+impl<const N: usize> BlockBuffer<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            buffer: [0u8; N],
+            length: 0,
+        }
+    }
+
+    /// Passes every full block that can be made from the buffered data and `data` to `compress`,
+    /// keeping any trailing partial block buffered for the next call.
+    pub(crate) fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; N])) {
+        // Top up a previously buffered partial block first.
+        if self.length > 0 {
+            let bytes = (N - self.length).min(data.len());
+            self.buffer[self.length..self.length + bytes].copy_from_slice(&data[0..bytes]);
+            self.length += bytes;
+            data = &data[bytes..];
+
+            if self.length < N {
+                return;
+            }
+
+            compress(&self.buffer);
+            self.length = 0;
+        }
+
+        let mut blocks = data.chunks_exact(N);
+        for block in &mut blocks {
+            // Cannot panic as chunks_exact always yields N byte slices.
+            compress(block.try_into().unwrap());
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[0..remainder.len()].copy_from_slice(remainder);
+        self.length = remainder.len();
+    }
+
+    /// Applies Merkle–Damgård padding: a single 1 bit, zero bits, then the encoded message
+    /// length filling the end of the final block.
+    pub(crate) fn pad(&mut self, encoded_length: &[u8], mut compress: impl FnMut(&[u8; N])) {
+        self.buffer[self.length] = 0x80;
+        self.length += 1;
+
+        // Use an extra block if the length no longer fits after the padding bit.
+        if self.length > N - encoded_length.len() {
+            self.buffer[self.length..].fill(0);
+            compress(&self.buffer);
+            self.length = 0;
+        }
+
+        self.buffer[self.length..N - encoded_length.len()].fill(0);
+        self.buffer[N - encoded_length.len()..].copy_from_slice(encoded_length);
+        compress(&self.buffer);
+        self.length = 0;
+    }
+}
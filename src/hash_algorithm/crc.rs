@@ -0,0 +1,596 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! Cyclic redundancy checks: the POSIX cksum CRC, CRC-32, CRC-32C and CRC-64/XZ.
+
+use crate::hash_algorithm::{Hash, Hasher, digest::Digest};
+
+// CRC used by POSIX cksum, processed most significant bit first.
+static POSIX: CrcModel = CrcModel::new(
+    32,
+    0x04_C1_1D_B7_u64,
+    false,
+    0,
+    0xFF_FF_FF_FF_u64,
+    Acceleration::CarrylessMultiply,
+);
+
+// CRC-32/ISO-HDLC as used by zlib, gzip, zip and PNG.
+static CRC32: CrcModel = CrcModel::new(
+    32,
+    0x04_C1_1D_B7_u64,
+    true,
+    0xFF_FF_FF_FF_u64,
+    0xFF_FF_FF_FF_u64,
+    Acceleration::CarrylessMultiply,
+);
+
+// CRC-32C (Castagnoli) as used by iSCSI, ext4 and btrfs.
+static CRC32C: CrcModel = CrcModel::new(
+    32,
+    0x1E_DC_6F_41_u64,
+    true,
+    0xFF_FF_FF_FF_u64,
+    0xFF_FF_FF_FF_u64,
+    Acceleration::Crc32cInstruction,
+);
+
+// CRC-64/XZ using the ECMA-182 polynomial as used by xz.
+static CRC64: CrcModel = CrcModel::new(
+    64,
+    0x42_F0_E1_EB_A9_EA_36_93_u64,
+    true,
+    0xFF_FF_FF_FF_FF_FF_FF_FF_u64,
+    0xFF_FF_FF_FF_FF_FF_FF_FF_u64,
+    Acceleration::CarrylessMultiply,
+);
+
+/// The POSIX cksum CRC.
+pub struct Crc {}
+
+/// CRC-32 as used by zlib, gzip and zip.
+pub struct Crc32 {}
+
+/// CRC-32C using the Castagnoli polynomial.
+pub struct Crc32c {}
+
+/// CRC-64/XZ using the ECMA-182 polynomial.
+pub struct Crc64 {}
+
+/// Instructions used to speed up a CRC when the target supports them. Without them every CRC
+/// falls back to slicing-by-8 tables.
+#[derive(Clone, Copy, PartialEq)]
+enum Acceleration {
+    // Folds 16 byte blocks with PCLMULQDQ, usable by any CRC.
+    CarrylessMultiply,
+    // The SSE4.2 crc32 instruction, which only implements CRC-32C.
+    Crc32cInstruction,
+}
+
+/// A CRC described by the parameters of the Rocksoft model, with the tables and constants
+/// needed to compute it generated at compile time.
+struct CrcModel {
+    width: u32,
+    reflected: bool,
+    init: u64,
+    xor_out: u64,
+    acceleration: Acceleration,
+    // Remainders of every byte value at each of the 8 byte offsets of a word. Reflected CRCs
+    // keep the register in the low bits, others keep it in the high bits so both use 64 bit
+    // shifts.
+    tables: [[u64; 256]; 8],
+    // x^192 mod P and x^128 mod P, used to fold the high and low halves of a 128 bit block over
+    // the next 128 bits.
+    fold_constants: [u64; 2],
+}
+
+/// Incremental state shared by every CRC hasher.
+#[derive(Clone)]
+struct CrcEngine {
+    model: &'static CrcModel,
+    register: u64,
+}
+
+/// Incremental POSIX cksum CRC hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct CrcHasher {
+    engine: CrcEngine,
+    total_data_size_bytes: u64,
+}
+
+/// Incremental CRC-32 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct Crc32Hasher(CrcEngine);
+
+/// Incremental CRC-32C hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct Crc32cHasher(CrcEngine);
+
+/// Incremental CRC-64 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct Crc64Hasher(CrcEngine);
+
+/// Returns x^`exponent` mod the polynomial, in normal bit order.
+const fn x_pow_mod(exponent: u32, width: u32, polynomial: u64) -> u64 {
+    let full_polynomial = (1_u128 << width) | polynomial as u128;
+    let mut remainder = 1_u128;
+    let mut i = 0;
+
+    while i < exponent {
+        remainder <<= 1;
+        if remainder >> width != 0 {
+            remainder ^= full_polynomial;
+        }
+        i += 1;
+    }
+
+    remainder as u64
+}
+
+impl CrcModel {
+    const fn new(
+        width: u32,
+        polynomial: u64,
+        reflected: bool,
+        init: u64,
+        xor_out: u64,
+        acceleration: Acceleration,
+    ) -> Self {
+        let mut tables = [[0u64; 256]; 8];
+        let mut i = 0;
+
+        while i < 256 {
+            let mut remainder = if reflected {
+                i as u64
+            } else {
+                (i as u64) << 56
+            };
+            let mut bit = 0;
+            while bit < 8 {
+                remainder = if reflected {
+                    let reflected_polynomial = polynomial.reverse_bits() >> (64 - width);
+                    if remainder & 1 != 0 {
+                        (remainder >> 1) ^ reflected_polynomial
+                    } else {
+                        remainder >> 1
+                    }
+                } else if remainder & (1 << 63) != 0 {
+                    (remainder << 1) ^ (polynomial << (64 - width))
+                } else {
+                    remainder << 1
+                };
+                bit += 1;
+            }
+            tables[0][i] = remainder;
+            i += 1;
+        }
+
+        // Each further table pushes the remainder of the previous one through one more zero byte.
+        let mut slice = 1;
+        while slice < 8 {
+            let mut i = 0;
+            while i < 256 {
+                let previous = tables[slice - 1][i];
+                tables[slice][i] = if reflected {
+                    (previous >> 8) ^ tables[0][(previous & 0xFF) as usize]
+                } else {
+                    (previous << 8) ^ tables[0][(previous >> 56) as usize]
+                };
+                i += 1;
+            }
+            slice += 1;
+        }
+
+        Self {
+            width,
+            reflected,
+            init,
+            xor_out,
+            acceleration,
+            tables,
+            // Carry-less multiplication of reflected values yields the product shifted left by one
+            // bit, which the reflected constants make up for by being one power lower.
+            fold_constants: if reflected {
+                [
+                    x_pow_mod(191, width, polynomial).reverse_bits(),
+                    x_pow_mod(127, width, polynomial).reverse_bits(),
+                ]
+            } else {
+                [
+                    x_pow_mod(192, width, polynomial),
+                    x_pow_mod(128, width, polynomial),
+                ]
+            },
+        }
+    }
+
+    fn initial_register(&self) -> u64 {
+        if self.reflected {
+            self.init.reverse_bits() >> (64 - self.width)
+        } else {
+            self.init << (64 - self.width)
+        }
+    }
+
+    fn update(&self, register: u64, data: &[u8]) -> u64 {
+        // The fallback is unreachable when the target supports every acceleration.
+        #[allow(unreachable_patterns)]
+        match self.acceleration {
+            #[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+            Acceleration::CarrylessMultiply => self.update_folding(register, data),
+            #[cfg(all(target_arch = "x86_64", target_feature = "sse4.2"))]
+            // SAFETY: SSE4.2 is enabled for the whole build.
+            Acceleration::Crc32cInstruction => unsafe { update_crc32c_instruction(register, data) },
+            _ => self.update_table(register, data),
+        }
+    }
+
+    /// Processes 8 bytes at a time with one table lookup per byte, then any remaining bytes one
+    /// at a time.
+    fn update_table(&self, mut register: u64, data: &[u8]) -> u64 {
+        let tables = &self.tables;
+        let mut words = data.chunks_exact(8);
+
+        for word in &mut words {
+            // The first byte of the word is the least significant for reflected CRCs and the
+            // most significant otherwise, and needs the table spanning the most bytes.
+            let value = if self.reflected {
+                (register ^ u64::from_le_bytes(word.try_into().unwrap())).to_le_bytes()
+            } else {
+                (register ^ u64::from_be_bytes(word.try_into().unwrap())).to_be_bytes()
+            };
+            register = tables[7][value[0] as usize]
+                ^ tables[6][value[1] as usize]
+                ^ tables[5][value[2] as usize]
+                ^ tables[4][value[3] as usize]
+                ^ tables[3][value[4] as usize]
+                ^ tables[2][value[5] as usize]
+                ^ tables[1][value[6] as usize]
+                ^ tables[0][value[7] as usize];
+        }
+
+        words.remainder().iter().fold(register, |register, byte| {
+            if self.reflected {
+                (register >> 8) ^ tables[0][((register ^ *byte as u64) & 0xFF) as usize]
+            } else {
+                (register << 8) ^ tables[0][((register >> 56) as u8 ^ byte) as usize]
+            }
+        })
+    }
+
+    /// Folds the message 16 bytes at a time with carry-less multiplication into a single 128 bit
+    /// block with the same remainder, leaving the last block and any partial block to the
+    /// tables.
+    #[cfg(all(target_arch = "x86_64", target_feature = "pclmulqdq"))]
+    fn update_folding(&self, register: u64, data: &[u8]) -> u64 {
+        use std::arch::x86_64::{__m128i, _mm_clmulepi64_si128, _mm_set_epi64x};
+
+        #[target_feature(enable = "sse2,pclmulqdq")]
+        fn multiply(a: u64, b: u64) -> u128 {
+            let product = _mm_clmulepi64_si128::<0x00>(
+                _mm_set_epi64x(0, a as i64),
+                _mm_set_epi64x(0, b as i64),
+            );
+            // SAFETY: __m128i and u128 are both plain 16 byte values.
+            unsafe { std::mem::transmute::<__m128i, u128>(product) }
+        }
+
+        if data.len() < 32 {
+            return self.update_table(register, data);
+        }
+
+        // Blocks are loaded so the first byte holds the highest degree coefficients, which is
+        // the least significant byte for reflected CRCs and the most significant otherwise.
+        let load = |block: &[u8]| {
+            let block = block.try_into().unwrap();
+            if self.reflected {
+                u128::from_le_bytes(block)
+            } else {
+                u128::from_be_bytes(block)
+            }
+        };
+
+        let mut blocks = data.chunks_exact(16);
+        // The register is equivalent to xoring it into the start of the message.
+        let mut folded = load(blocks.next().unwrap())
+            ^ if self.reflected {
+                register as u128
+            } else {
+                (register as u128) << 64
+            };
+        for block in &mut blocks {
+            let (high_half, low_half) = if self.reflected {
+                (folded as u64, (folded >> 64) as u64)
+            } else {
+                ((folded >> 64) as u64, folded as u64)
+            };
+            // SAFETY: PCLMULQDQ is enabled for the whole build.
+            let (high, low) = unsafe {
+                (
+                    multiply(high_half, self.fold_constants[0]),
+                    multiply(low_half, self.fold_constants[1]),
+                )
+            };
+            folded = high ^ low ^ load(block);
+        }
+
+        let folded = if self.reflected {
+            folded.to_le_bytes()
+        } else {
+            folded.to_be_bytes()
+        };
+        let register = self.update_table(0, &folded);
+        self.update_table(register, blocks.remainder())
+    }
+
+    fn finalize(&self, register: u64) -> u64 {
+        if self.reflected {
+            register ^ self.xor_out
+        } else {
+            (register >> (64 - self.width)) ^ self.xor_out
+        }
+    }
+}
+
+/// CRC-32C using the SSE4.2 crc32 instruction on 8 bytes at a time.
+#[cfg(all(target_arch = "x86_64", target_feature = "sse4.2"))]
+#[target_feature(enable = "sse4.2")]
+fn update_crc32c_instruction(mut register: u64, data: &[u8]) -> u64 {
+    use std::arch::x86_64::{_mm_crc32_u8, _mm_crc32_u64};
+
+    let mut words = data.chunks_exact(8);
+    for word in &mut words {
+        register = _mm_crc32_u64(register, u64::from_le_bytes(word.try_into().unwrap()));
+    }
+
+    words.remainder().iter().fold(register, |register, byte| {
+        _mm_crc32_u8(register as u32, *byte) as u64
+    })
+}
+
+impl CrcEngine {
+    fn new(model: &'static CrcModel) -> Self {
+        Self {
+            model,
+            register: model.initial_register(),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.register = self.model.update(self.register, data);
+    }
+
+    /// Returns the CRC as big endian bytes, the width of the CRC long.
+    fn finalize(self) -> Digest {
+        let crc = self.model.finalize(self.register).to_be_bytes();
+
+        Digest::from(crc[8 - self.model.width as usize / 8..].to_vec())
+    }
+}
+
+impl Hasher for CrcHasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self {
+            engine: CrcEngine::new(&POSIX),
+            total_data_size_bytes: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+        self.total_data_size_bytes += data.len() as u64;
+    }
+
+    /// Returns the CRC as 4 big endian bytes.
+    fn finalize(mut self) -> Digest {
+        // POSIX appends the message length to the message, least significant byte first and
+        // using as few bytes as possible.
+        let length = self.total_data_size_bytes.to_le_bytes();
+        let length_bytes = 8 - self.total_data_size_bytes.leading_zeros() as usize / 8;
+        self.engine.update(&length[..length_bytes]);
+
+        self.engine.finalize()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for Crc32Hasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self(CrcEngine::new(&CRC32))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Returns the CRC as 4 big endian bytes.
+    fn finalize(self) -> Digest {
+        self.0.finalize()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for Crc32cHasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self(CrcEngine::new(&CRC32C))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Returns the CRC as 4 big endian bytes.
+    fn finalize(self) -> Digest {
+        self.0.finalize()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for Crc64Hasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self(CrcEngine::new(&CRC64))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    /// Returns the CRC as 8 big endian bytes.
+    fn finalize(self) -> Digest {
+        self.0.finalize()
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hash for Crc {
+    type Hasher = CrcHasher;
+}
+
+impl Hash for Crc32 {
+    type Hasher = Crc32Hasher;
+}
+
+impl Hash for Crc32c {
+    type Hasher = Crc32cHasher;
+}
+
+impl Hash for Crc64 {
+    type Hasher = Crc64Hasher;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crc(message: &[u8]) -> u32 {
+        u32::from_be_bytes(Crc::hash_slice(message).as_bytes().try_into().unwrap())
+    }
+
+    fn test_message() -> Vec<u8> {
+        (0..1000_u32).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn empty_hash() {
+        assert_eq!(crc(&[]), 4294967295);
+        assert_eq!(Crc32::hash_slice(&[]).to_hex(), "00000000");
+        assert_eq!(Crc32c::hash_slice(&[]).to_hex(), "00000000");
+        assert_eq!(Crc64::hash_slice(&[]).to_hex(), "0000000000000000");
+    }
+
+    #[test]
+    fn posix_hash_suite() {
+        // Values from GNU coreutils cksum.
+        assert_eq!(crc("abc".as_bytes()), 1219131554);
+        assert_eq!(crc("123456789".as_bytes()), 930766865);
+        assert_eq!(crc(&[0u8; 1025]), 1055017215);
+    }
+
+    #[test]
+    fn crc_catalogue_hash_suite() {
+        // Check values from the catalogue of parametrised CRC algorithms.
+        let check = "123456789".as_bytes();
+        assert_eq!(Crc32::hash_slice(check).to_hex(), "cbf43926");
+        assert_eq!(Crc32c::hash_slice(check).to_hex(), "e3069283");
+        assert_eq!(Crc64::hash_slice(check).to_hex(), "995dc9bbdf1939fa");
+
+        assert_eq!(Crc32::hash_slice("abc".as_bytes()).to_hex(), "352441c2");
+        assert_eq!(Crc32c::hash_slice("abc".as_bytes()).to_hex(), "364b3fb7");
+        assert_eq!(
+            Crc64::hash_slice("abc".as_bytes()).to_hex(),
+            "2cd8094a1a277627"
+        );
+
+        // Long enough to be folded, with a partial block left over.
+        assert_eq!(Crc32::hash_slice(&test_message()).to_hex(), "74e3fb41");
+        assert_eq!(Crc32c::hash_slice(&test_message()).to_hex(), "1a318e30");
+        assert_eq!(
+            Crc64::hash_slice(&test_message()).to_hex(),
+            "ec6ed4d8103b4e4e"
+        );
+
+        assert_eq!(Crc32::hash_slice(&[0u8; 4096]).to_hex(), "c71c0011");
+        assert_eq!(Crc32c::hash_slice(&[0u8; 4096]).to_hex(), "98f94189");
+        assert_eq!(Crc64::hash_slice(&[0u8; 4096]).to_hex(), "26d3d39425eaf0a5");
+    }
+
+    #[test]
+    fn accelerated_matches_table() {
+        let test_vec = test_message();
+
+        for model in [&POSIX, &CRC32, &CRC32C, &CRC64] {
+            for length in 0..=test_vec.len() {
+                let register = model.initial_register() ^ length as u64;
+                assert_eq!(
+                    model.update(register, &test_vec[..length]),
+                    model.update_table(register, &test_vec[..length])
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec = test_message();
+        let expected = [
+            Crc::hash_slice(&test_vec),
+            Crc32::hash_slice(&test_vec),
+            Crc32c::hash_slice(&test_vec),
+            Crc64::hash_slice(&test_vec),
+        ];
+
+        for chunk_size in 1..=100 {
+            let mut crc_hasher = CrcHasher::new();
+            let mut crc32_hasher = Crc32Hasher::new();
+            let mut crc32c_hasher = Crc32cHasher::new();
+            let mut crc64_hasher = Crc64Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                crc_hasher.update(chunk);
+                crc32_hasher.update(chunk);
+                crc32c_hasher.update(chunk);
+                crc64_hasher.update(chunk);
+            }
+
+            assert_eq!(
+                [
+                    crc_hasher.finalize(),
+                    crc32_hasher.finalize(),
+                    crc32c_hasher.finalize(),
+                    crc64_hasher.finalize(),
+                ],
+                expected
+            );
+        }
+
+        let mut hasher = Crc64Hasher::new();
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("123456789".as_bytes());
+
+        assert_eq!(hasher.finalize().to_hex(), "995dc9bbdf1939fa");
+    }
+}
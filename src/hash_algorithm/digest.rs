@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! The digest type returned by every algorithm, with hex and base64 encodings.
+
+use std::fmt::{self, Display};
+
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+// Alphabets from rfc 4648 sections 4 and 5.
+const BASE64_STANDARD: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Raw bytes produced by a hash function, with encoders for the textual forms used in checksum
+/// files.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Digest(Vec<u8>);
+
+fn encode_hex(bytes: &[u8], alphabet: &[u8; 16]) -> String {
+    let mut return_string = String::with_capacity(bytes.len() * 2);
+
+    for byte in bytes {
+        return_string.push(alphabet[(byte >> 4) as usize] as char);
+        return_string.push(alphabet[(byte & 0x0F) as usize] as char);
+    }
+
+    return_string
+}
+
+fn encode_base64(bytes: &[u8], alphabet: &[u8; 64]) -> String {
+    let mut return_string = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        // Pack up to 3 bytes into the top 24 bits, then split into four 6 bit groups.
+        let group = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                return_string.push(alphabet[(group >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                return_string.push('=');
+            }
+        }
+    }
+
+    return_string
+}
+
+impl Digest {
+    /// Returns the raw digest bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the length of the digest in bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if the digest contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Encodes the digest as lowercase hexadecimal.
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.0, HEX_LOWER)
+    }
+
+    /// Encodes the digest as uppercase hexadecimal.
+    pub fn to_upper_hex(&self) -> String {
+        encode_hex(&self.0, HEX_UPPER)
+    }
+
+    /// Encodes the digest as padded base64 using the standard alphabet from rfc 4648.
+    pub fn to_base64(&self) -> String {
+        encode_base64(&self.0, BASE64_STANDARD)
+    }
+
+    /// Encodes the digest as padded base64 using the URL and filename safe alphabet from
+    /// rfc 4648.
+    pub fn to_base64url(&self) -> String {
+        encode_base64(&self.0, BASE64_URL)
+    }
+
+    /// Decodes a digest from hexadecimal of either case. Returns None if `hex` has an odd length
+    /// or contains non hex characters.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if !hex.len().is_multiple_of(2) {
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity(hex.len() / 2);
+        for pair in hex.as_bytes().chunks_exact(2) {
+            let high = (pair[0] as char).to_digit(16)?;
+            let low = (pair[1] as char).to_digit(16)?;
+            bytes.push((high << 4 | low) as u8);
+        }
+
+        Some(Self(bytes))
+    }
+
+    /// Decodes a digest from padded base64 using the standard alphabet from rfc 4648. Returns
+    /// None if `base64` is not canonically encoded.
+    pub fn from_base64(base64: &str) -> Option<Self> {
+        if !base64.len().is_multiple_of(4) {
+            return None;
+        }
+
+        let mut bytes = Vec::with_capacity(base64.len() / 4 * 3);
+        let mut quartets = base64.as_bytes().chunks_exact(4).peekable();
+        while let Some(quartet) = quartets.next() {
+            // Padding is only allowed at the end of the final quartet.
+            let padding = quartet.iter().rev().take_while(|&&c| c == b'=').count();
+            if padding > 2 || (padding > 0 && quartets.peek().is_some()) {
+                return None;
+            }
+
+            let mut group = 0u32;
+            for &character in &quartet[0..4 - padding] {
+                let value = BASE64_STANDARD.iter().position(|&c| c == character)?;
+                group = group << 6 | value as u32;
+            }
+            group <<= 6 * padding;
+
+            let decoded = &group.to_be_bytes()[1..4 - padding];
+            // Reject encodings with non zero bits after the final byte.
+            if padding > 0 && group.to_be_bytes()[4 - padding] != 0 {
+                return None;
+            }
+            bytes.extend_from_slice(decoded);
+        }
+
+        Some(Self(bytes))
+    }
+}
+
+impl From<Vec<u8>> for Digest {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<&[u8]> for Digest {
+    fn from(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+impl AsRef<[u8]> for Digest {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_encoding() {
+        let digest = Digest::from(vec![0x00, 0x1F, 0xA0, 0xFF]);
+
+        assert_eq!(digest.to_hex(), "001fa0ff");
+        assert_eq!(digest.to_upper_hex(), "001FA0FF");
+        assert_eq!(digest.to_string(), "001fa0ff");
+        assert_eq!(Digest::from_hex("001fa0ff"), Some(digest.clone()));
+        assert_eq!(Digest::from_hex("001FA0FF"), Some(digest));
+        assert_eq!(Digest::from_hex("001fa0f"), None);
+        assert_eq!(Digest::from_hex("001fa0fg"), None);
+    }
+
+    #[test]
+    fn rfc_base64_suite() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+
+        for (input, output) in vectors {
+            let digest = Digest::from(input.as_bytes());
+
+            assert_eq!(digest.to_base64(), output);
+            assert_eq!(digest.to_base64url(), output);
+            assert_eq!(Digest::from_base64(output), Some(digest));
+        }
+    }
+
+    #[test]
+    fn invalid_base64() {
+        assert_eq!(Digest::from_base64("Zg="), None);
+        assert_eq!(Digest::from_base64("Z==="), None);
+        assert_eq!(Digest::from_base64("Zg==Zg=="), None);
+        assert_eq!(Digest::from_base64("Zh=="), None);
+        assert_eq!(Digest::from_base64("Zm9-"), None);
+    }
+
+    #[test]
+    fn base64_alphabets() {
+        let digest = Digest::from(vec![0xFB, 0xFF, 0xBF]);
+
+        assert_eq!(digest.to_base64(), "+/+/");
+        assert_eq!(digest.to_base64url(), "-_-_");
+    }
+}
@@ -0,0 +1,341 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! Keyed-hash message authentication codes built on any [`CryptographicHash`].
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hasher, VariableOutputHasher, digest::Digest, update_from_stream,
+};
+use std::io::Read;
+
+// Bytes xored into the padded key for the inner and outer hashes as defined in rfc 2104
+// section 2.
+const INNER_PAD: u8 = 0x36;
+const OUTER_PAD: u8 = 0x5C;
+
+/// Keyed-hash message authentication code as defined in rfc 2104, computed over the data passed
+/// to `update` with the hash algorithm `H`.
+///
+/// Checksums aren't cryptographic hashes, so an HMAC can't be built on them:
+///
+/// ```compile_fail
+/// use hashsum::hash_algorithm::{crc::Crc32, hmac::Hmac};
+///
+/// let mac = Hmac::<Crc32>::hash_slice(b"key", b"abc");
+/// ```
+pub struct Hmac<H: CryptographicHash> {
+    inner: H::Hasher,
+    outer: H::Hasher,
+}
+
+impl<H: CryptographicHash> Hmac<H> {
+    /// Creates an HMAC using `key`, which may be of any length.
+    pub fn new(key: &[u8]) -> Self {
+        Self::with_hasher(key, H::Hasher::new)
+    }
+
+    /// Creates an HMAC whose inner and outer hashes, and the hash of a long key, are all
+    /// produced by hashers created with `new_hasher`, such as hashers with a non-default
+    /// configuration.
+    pub fn with_hasher(key: &[u8], new_hasher: impl Fn() -> H::Hasher) -> Self {
+        let block_size = H::Hasher::BLOCK_SIZE;
+
+        // Keys longer than a block are hashed first, then every key is zero padded to a block.
+        let mut padded_key = if key.len() > block_size {
+            let mut hasher = new_hasher();
+            hasher.update(key);
+            hasher.finalize().as_bytes().to_vec()
+        } else {
+            key.to_vec()
+        };
+        padded_key.resize(block_size, 0);
+
+        let mut inner = new_hasher();
+        let inner_key: Vec<u8> = padded_key.iter().map(|byte| byte ^ INNER_PAD).collect();
+        inner.update(&inner_key);
+
+        let mut outer = new_hasher();
+        let outer_key: Vec<u8> = padded_key.iter().map(|byte| byte ^ OUTER_PAD).collect();
+        outer.update(&outer_key);
+
+        Self { inner, outer }
+    }
+
+    /// Processes `data` as part of the authenticated message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Returns the HMAC of everything passed to `update`.
+    pub fn finalize(mut self) -> Digest {
+        let inner_digest = self.inner.finalize();
+        self.outer.update(inner_digest.as_bytes());
+        self.outer.finalize()
+    }
+
+    /// Returns the HMAC of `message` using `key`.
+    pub fn hash_slice(key: &[u8], message: &[u8]) -> Digest {
+        let mut hmac = Self::new(key);
+        hmac.update(message);
+        hmac.finalize()
+    }
+
+    /// Returns the HMAC of `stream` using `key`.
+    pub fn hash_stream(key: &[u8], stream: impl Read) -> std::io::Result<Digest> {
+        Self::new(key).finalize_stream(stream)
+    }
+
+    /// Processes `stream` until its end as part of the message and returns the HMAC.
+    pub fn finalize_stream(mut self, stream: impl Read) -> std::io::Result<Digest> {
+        update_from_stream(stream, H::Hasher::STREAM_BUFFER_SIZE, |data| {
+            self.update(data)
+        })?;
+
+        Ok(self.finalize())
+    }
+}
+
+impl<H: CryptographicHash> Hmac<H>
+where
+    H::Hasher: VariableOutputHasher,
+{
+    /// Creates an HMAC using `key` on top of hashes of `output_bits` bits. Panics if the length
+    /// is unsupported by the algorithm.
+    pub fn with_output_bits(key: &[u8], output_bits: usize) -> Self {
+        H::Hasher::assert_output_bits(output_bits);
+
+        Self::with_hasher(key, || H::Hasher::with_output_bits(output_bits))
+    }
+
+    /// Returns the HMAC of `message` using `key` on top of hashes of `output_bits` bits.
+    pub fn hash_slice_with_output_bits(key: &[u8], message: &[u8], output_bits: usize) -> Digest {
+        let mut hmac = Self::with_output_bits(key, output_bits);
+        hmac.update(message);
+        hmac.finalize()
+    }
+
+    /// Returns the HMAC of `stream` using `key` on top of hashes of `output_bits` bits.
+    pub fn hash_stream_with_output_bits(
+        key: &[u8],
+        stream: impl Read,
+        output_bits: usize,
+    ) -> std::io::Result<Digest> {
+        Self::with_output_bits(key, output_bits).finalize_stream(stream)
+    }
+}
+
+// The test vectors cover several algorithms, so they only run with all of them enabled.
+#[cfg(all(
+    test,
+    feature = "md5",
+    feature = "sha1",
+    feature = "sha2",
+    feature = "sha3",
+    feature = "blake2"
+))]
+mod tests {
+    use super::*;
+    use crate::hash_algorithm::{
+        blake2b::BLAKE2b,
+        md5::MD5,
+        sha1::SHA1,
+        sha3::SHA3_256,
+        sha256::{SHA224, SHA256},
+        sha512::{SHA384, SHA512},
+    };
+
+    /// The keys and messages of test cases 1 to 7 shared by rfc 2202 and rfc 4231, which use
+    /// different key lengths and a different final message.
+    fn rfc_test_cases(key_length: usize, large_key_length: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let last_message = if large_key_length == 80 {
+            "Test Using Larger Than Block-Size Key and Larger Than One Block-Size Data"
+        } else {
+            "This is a test using a larger than block-size key and a larger than block-size data. \
+             The key needs to be hashed before being used by the HMAC algorithm."
+        };
+
+        vec![
+            (vec![0x0B; key_length], "Hi There".as_bytes().to_vec()),
+            (
+                "Jefe".as_bytes().to_vec(),
+                "what do ya want for nothing?".as_bytes().to_vec(),
+            ),
+            (vec![0xAA; key_length], vec![0xDD; 50]),
+            ((0x01..=0x19).collect(), vec![0xCD; 50]),
+            (
+                vec![0x0C; key_length],
+                "Test With Truncation".as_bytes().to_vec(),
+            ),
+            (
+                vec![0xAA; large_key_length],
+                "Test Using Larger Than Block-Size Key - Hash Key First"
+                    .as_bytes()
+                    .to_vec(),
+            ),
+            (
+                vec![0xAA; large_key_length],
+                last_message.as_bytes().to_vec(),
+            ),
+        ]
+    }
+
+    fn assert_rfc_test_cases<H: CryptographicHash>(
+        key_length: usize,
+        large_key_length: usize,
+        expected: [&str; 7],
+    ) {
+        for ((key, message), expected) in rfc_test_cases(key_length, large_key_length)
+            .iter()
+            .zip(expected)
+        {
+            // Test case 5 of rfc 4231 only gives the first 128 bits.
+            let digest = Hmac::<H>::hash_slice(key, message).to_hex();
+            assert_eq!(&digest[..expected.len()], expected);
+        }
+    }
+
+    #[test]
+    fn empty_hash() {
+        assert_eq!(
+            Hmac::<SHA256>::hash_slice(&[], &[]).to_hex(),
+            "b613679a0814d9ec772f95d778c35fc5ff1697c493715653c6c712144292c5ad"
+        );
+    }
+
+    #[test]
+    fn rfc_hash_suite() {
+        // Test cases from rfc 2202 section 2 and 3.
+        assert_rfc_test_cases::<MD5>(
+            16,
+            80,
+            [
+                "9294727a3638bb1c13f48ef8158bfc9d",
+                "750c783e6ab0b503eaa86e310a5db738",
+                "56be34521d144c88dbb8c733f0e8b3f6",
+                "697eaf0aca3a3aea3a75164746ffaa79",
+                "56461ef2342edc00f9bab995690efd4c",
+                "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd",
+                "6f630fad67cda0ee1fb1f562db3aa53e",
+            ],
+        );
+        assert_rfc_test_cases::<SHA1>(
+            20,
+            80,
+            [
+                "b617318655057264e28bc0b6fb378c8ef146be00",
+                "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79",
+                "125d7342b9ac11cd91a39af48aa17b4f63f175d3",
+                "4c9007f4026250c6bc8414f9bf50c86c2d7235da",
+                "4c1a03424b55e07fe7f27be1d58bb9324a9a5a04",
+                "aa4ae5e15272d00e95705637ce8a3b55ed402112",
+                "e8e99d0f45237d786d6bbaa7965c7808bbff1a91",
+            ],
+        );
+
+        // Test cases from rfc 4231 section 4.
+        assert_rfc_test_cases::<SHA224>(
+            20,
+            131,
+            [
+                "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22",
+                "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+                "7fb3cb3588c6c1f6ffa9694d7d6ad2649365b0c1f65d69d1ec8333ea",
+                "6c11506874013cac6a2abc1bb382627cec6a90d86efc012de7afec5a",
+                "0e2aea68a90c8d37c988bcdb9fca6fa8",
+                "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+                "3a854166ac5d9f023f54d517d0b39dbd946770db9c2b95c9f6f565d1",
+            ],
+        );
+        assert_rfc_test_cases::<SHA256>(
+            20,
+            131,
+            [
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                "a3b6167473100ee06e0c796c2955552b",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+            ],
+        );
+        assert_rfc_test_cases::<SHA384>(
+            20,
+            131,
+            [
+                "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+                "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+                "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9febe83ef4e55966144b2a5ab39dc13814b94e3ab6e101a34f27",
+                "3e8a69b7783c25851933ab6290af6ca77a9981480850009cc5577c6e1f573b4e6801dd23c4a7d679ccf8a386c674cffb",
+                "3abf34c3503b2a23a46efc619baef897",
+                "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+                "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9adccebb82461e99c5a678cc31e799176d3860e6110c46523e",
+            ],
+        );
+        assert_rfc_test_cases::<SHA512>(
+            20,
+            131,
+            [
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+                "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+                "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+                "415fad6271580a531d4179bc891d87a6",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+                "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+            ],
+        );
+    }
+
+    #[test]
+    fn other_algorithms() {
+        // Values from Python's hmac module, which uses the same block sizes.
+        let key = "Jefe".as_bytes();
+        let message = "what do ya want for nothing?".as_bytes();
+
+        assert_eq!(
+            Hmac::<SHA3_256>::hash_slice(key, message).to_hex(),
+            "c7d4072e788877ae3596bbb0da73b887c9171f93095b294ae857fbe2645e1ba5"
+        );
+        assert_eq!(
+            Hmac::<SHA3_256>::hash_slice(&[0xAA; 200], "Hi There".as_bytes()).to_hex(),
+            "63f1b35c7152a5b61aea75465df24cebd1e6996c0a67905ea8c499b07f600354"
+        );
+        assert_eq!(
+            Hmac::<BLAKE2b>::hash_slice(key, message).to_hex(),
+            "6ff884f8ddc2a6586b3c98a4cd6ebdf14ec10204b6710073eb5865ade37a2643b8807c1335d107ecdb9ffeaeb6828c4625ba172c66379efcd222c2de11727ab4"
+        );
+
+        // The long key is hashed to the shorter output length as well.
+        assert_eq!(
+            Hmac::<BLAKE2b>::hash_slice_with_output_bits(key, message, 256).to_hex(),
+            "3cf096eeeb2202a250db168c4823a44ef4618ebabb225789386fed316131e3a0"
+        );
+        assert_eq!(
+            Hmac::<BLAKE2b>::hash_slice_with_output_bits(&[0xAA; 200], "Hi There".as_bytes(), 256)
+                .to_hex(),
+            "c659c0d16b26d89540ba22bcedc2c72473078f9e968af0936624785f1a4d5316"
+        );
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let key = [0xAA; 131];
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = Hmac::<SHA256>::hash_slice(&key, &test_vec);
+
+        for chunk_size in 1..=100 {
+            let mut hmac = Hmac::<SHA256>::new(&key);
+            for chunk in test_vec.chunks(chunk_size) {
+                hmac.update(chunk);
+            }
+
+            assert_eq!(hmac.finalize(), expected);
+        }
+
+        assert_eq!(
+            Hmac::<SHA256>::hash_stream(&key, test_vec.as_slice()).unwrap(),
+            expected
+        );
+    }
+}
@@ -1,7 +1,11 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
 
-use crate::hash_algorithm::Hash;
+//! MD5 as defined in rfc 1321.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, block_buffer::BlockBuffer, digest::Digest,
+};
 
 // 64 constants calculated as 'Let T[i] denote the i-th element of the table, which is equal to the integer part
 // of 4294967296 times abs(sin(i)), where i is in radians.' in rfc 1321.
@@ -79,184 +83,114 @@ const SHIFTS: [u32; 64] = [
     21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
 ];
 
+/// The MD5 message digest.
 pub struct MD5 {}
 
-struct MD5Context {
-    a: u32,
-    b: u32,
-    c: u32,
-    d: u32,
-    total_data_size_bits: u64,
-    non_zero_padding_required: bool,
-    padded: bool,
-    hash: Option<String>,
+/// Incremental MD5 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct MD5Hasher {
+    state: [u32; 4],
+    buffer: BlockBuffer<64>,
+    total_data_size_bytes: u64,
 }
 
-impl Default for MD5Context {
-    fn default() -> Self {
-        Self {
-            a: 0x67_45_23_01_u32,
-            b: 0xEF_CD_AB_89_u32,
-            c: 0x98_BA_DC_FE_u32,
-            d: 0x10_32_54_76_u32,
-            total_data_size_bits: 0,
-            non_zero_padding_required: true,
-            padded: false,
-            hash: None,
-        }
+fn compress(state: &mut [u32; 4], data_block: &[u8; 64]) {
+    // Load 512 bit data block into 16 little-endian 32 bit words.
+    let mut words = [0u32; 16];
+    for (word, bytes) in words.iter_mut().zip(data_block.chunks_exact(4)) {
+        *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
     }
-}
-
-impl MD5 {
-    fn hash_block(mut context: MD5Context, original_data_block: &[u8]) -> MD5Context {
-        assert!(
-            original_data_block.len() <= 64,
-            "Too large of an array passed to hash block. Must be 64 bytes or less."
-        );
-
-        let mut data_block = original_data_block;
-        let mut temp_vec = Vec::new();
-
-        context.total_data_size_bits += (8 * original_data_block.len()) as u64;
-
-        // Pad only is block is less than 512 bits.
-        if original_data_block.len() != 64 {
-            // Add padding
-            let mut padding = [0x00_u8; 56];
-            let mut padding_bytes = 56;
-
-            // Add non zero padding unless current block has no data / end of stream.
-            if context.non_zero_padding_required {
-                // Add 0b10000000 to start of padding.
-                padding[0] = 0x80;
-            }
-
-            // Pad to full 512 bit block and change context if next block needs to be a padding
-            // block.
-            if original_data_block.len() < 56 {
-                padding_bytes -= original_data_block.len();
-            } else {
-                padding_bytes = 64 - original_data_block.len();
-                context.non_zero_padding_required = false;
-            }
-
-            // Add original data and padding to temp vec
-            temp_vec.extend_from_slice(original_data_block);
-            temp_vec.extend(&padding[0..padding_bytes]);
-
-            // Add original size of message in bits if block is last block.
-            if original_data_block.len() < 56 {
-                temp_vec.extend(context.total_data_size_bits.to_le_bytes());
-                context.padded = true;
-            }
-
-            data_block = temp_vec.as_slice();
-        }
-
-        // Load 512 bit data block into 16 little-endian 32 bit words.
-        let mut words = [0u32; 16];
-        for i in 0..16 {
-            words[i] = u32::from_le_bytes([
-                data_block[i * 4],
-                data_block[i * 4 + 1],
-                data_block[i * 4 + 2],
-                data_block[i * 4 + 3],
-            ]);
-        }
-
-        let mut temp_a = context.a;
-        let mut temp_b = context.b;
-        let mut temp_c = context.c;
-        let mut temp_d = context.d;
-
-        for i in 0..64 {
-            let mut f: u32;
-            let g: u32;
-
-            if i < 16 {
-                f = (temp_b & temp_c) | (!temp_b & temp_d);
-                g = i;
-            } else if i < 32 {
-                f = (temp_d & temp_b) | (!temp_d & temp_c);
-                g = (5 * i + 1) % 16;
-            } else if i < 48 {
-                f = temp_b ^ temp_c ^ temp_d;
-                g = (3 * i + 5) % 16;
-            } else {
-                f = temp_c ^ (temp_b | !temp_d);
-                g = (7 * i) % 16;
-            }
-
-            //f += temp_a + constants[i as usize] + data_block[g as usize];
-            f = f
-                .wrapping_add(temp_a)
-                .wrapping_add(CONSTANTS[i as usize])
-                .wrapping_add(words[g as usize]);
-            temp_a = temp_d;
-            temp_d = temp_c;
-            temp_c = temp_b;
-            //temp_b += f << rotate_left(SHIFTS[i as usize]);
-            temp_b = temp_b.wrapping_add(f.rotate_left(SHIFTS[i as usize]));
-        }
 
-        context.a = context.a.wrapping_add(temp_a);
-        context.b = context.b.wrapping_add(temp_b);
-        context.c = context.c.wrapping_add(temp_c);
-        context.d = context.d.wrapping_add(temp_d);
-
-        if context.padded {
-            // message_digest of 128 bits.
-            let mut message_digest = Vec::with_capacity(16);
-            message_digest.extend_from_slice(&context.a.to_le_bytes());
-            message_digest.extend_from_slice(&context.b.to_le_bytes());
-            message_digest.extend_from_slice(&context.c.to_le_bytes());
-            message_digest.extend_from_slice(&context.d.to_le_bytes());
-
-            let mut return_string = String::new();
-            for byte in message_digest.iter() {
-                return_string.push_str(&format!("{:02x}", byte));
-            }
-            context.hash = Some(return_string);
+    let [mut temp_a, mut temp_b, mut temp_c, mut temp_d] = *state;
+
+    for i in 0..64 {
+        let mut f: u32;
+        let g: u32;
+
+        if i < 16 {
+            f = (temp_b & temp_c) | (!temp_b & temp_d);
+            g = i;
+        } else if i < 32 {
+            f = (temp_d & temp_b) | (!temp_d & temp_c);
+            g = (5 * i + 1) % 16;
+        } else if i < 48 {
+            f = temp_b ^ temp_c ^ temp_d;
+            g = (3 * i + 5) % 16;
+        } else {
+            f = temp_c ^ (temp_b | !temp_d);
+            g = (7 * i) % 16;
         }
 
-        return context;
+        //f += temp_a + constants[i as usize] + data_block[g as usize];
+        f = f
+            .wrapping_add(temp_a)
+            .wrapping_add(CONSTANTS[i as usize])
+            .wrapping_add(words[g as usize]);
+        temp_a = temp_d;
+        temp_d = temp_c;
+        temp_c = temp_b;
+        //temp_b += f << rotate_left(SHIFTS[i as usize]);
+        temp_b = temp_b.wrapping_add(f.rotate_left(SHIFTS[i as usize]));
     }
+
+    state[0] = state[0].wrapping_add(temp_a);
+    state[1] = state[1].wrapping_add(temp_b);
+    state[2] = state[2].wrapping_add(temp_c);
+    state[3] = state[3].wrapping_add(temp_d);
 }
 
-impl Hash for MD5 {
-    fn hash_slice(message: &[u8]) -> String {
-        let mut context: MD5Context = Default::default();
+impl Hasher for MD5Hasher {
+    const BLOCK_SIZE: usize = 64;
 
-        for chunk in message.chunks(64) {
-            context = Self::hash_block(context, chunk);
+    fn new() -> Self {
+        Self {
+            state: [
+                0x67_45_23_01_u32,
+                0xEF_CD_AB_89_u32,
+                0x98_BA_DC_FE_u32,
+                0x10_32_54_76_u32,
+            ],
+            buffer: BlockBuffer::new(),
+            total_data_size_bytes: 0,
         }
+    }
 
-        if context.hash == None {
-            context = Self::hash_block(context, &[])
-        }
+    fn update(&mut self, data: &[u8]) {
+        self.total_data_size_bytes = self.total_data_size_bytes.wrapping_add(data.len() as u64);
 
-        // Cannot panic as a hash will always be produced.
-        return context.hash.unwrap();
+        self.buffer
+            .update(data, |block| compress(&mut self.state, block));
     }
 
-    fn hash_stream(mut stream: impl std::io::Read) -> std::io::Result<String> {
-        let mut context: MD5Context = Default::default();
-        let mut buffer = [0u8; 64];
+    fn finalize(mut self) -> Digest {
+        // Original size of message in bits is appended little-endian.
+        let total_data_size_bits = self.total_data_size_bytes.wrapping_mul(8);
 
-        loop {
-            let bytes = stream.read(&mut buffer)?;
-            context = Self::hash_block(context, &buffer[0..bytes]);
+        self.buffer
+            .pad(&total_data_size_bits.to_le_bytes(), |block| {
+                compress(&mut self.state, block)
+            });
 
-            if bytes < 64 {
-                break;
-            }
+        // message_digest of 128 bits.
+        let mut message_digest = Vec::with_capacity(16);
+        for word in self.state {
+            message_digest.extend_from_slice(&word.to_le_bytes());
         }
 
-        // Cannot panic as a hash will always be produced.
-        return Ok(context.hash.unwrap());
+        Digest::from(message_digest)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
     }
 }
 
+impl Hash for MD5 {
+    type Hasher = MD5Hasher;
+}
+
+impl CryptographicHash for MD5 {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,7 +200,7 @@ mod tests {
         let test_vec = Vec::new();
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "d41d8cd98f00b204e9800998ecf8427e"
         );
     }
@@ -277,7 +211,7 @@ mod tests {
         test_vec.extend_from_slice("a".as_bytes());
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "0cc175b9c0f1b6a831c399e269772661"
         );
 
@@ -285,7 +219,7 @@ mod tests {
         test_vec.extend_from_slice("abc".as_bytes());
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "900150983cd24fb0d6963f7d28e17f72"
         );
 
@@ -293,7 +227,7 @@ mod tests {
         test_vec.extend_from_slice("message digest".as_bytes());
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "f96b697d7cb7938d525a2f31aaf161d0"
         );
 
@@ -301,7 +235,7 @@ mod tests {
         test_vec.extend_from_slice("abcdefghijklmnopqrstuvwxyz".as_bytes());
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "c3fcd3d76192e4007dfb496cca67e13b"
         );
 
@@ -311,7 +245,7 @@ mod tests {
         );
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "d174ab98d277d9f5a5611c2c9f419d9f"
         );
 
@@ -322,8 +256,34 @@ mod tests {
         );
 
         assert_eq!(
-            MD5::hash_slice(&test_vec),
+            MD5::hash_slice(&test_vec).to_hex(),
             "57edf4a22be3c955ac49da2e2107b67a"
         );
     }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = MD5::hash_slice(&test_vec);
+
+        // Split the message at every chunk size up to and past the block size.
+        for chunk_size in 1..=300 {
+            let mut hasher = MD5Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = MD5Hasher::new();
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(
+            hasher.finalize().to_hex(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+    }
 }
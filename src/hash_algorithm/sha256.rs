@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! SHA-224 and SHA-256 as defined in FIPS 180-4.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, block_buffer::BlockBuffer, digest::Digest,
+};
+
+// First 32 bits of the fractional parts of the cube roots of the first 64 primes as defined in
+// FIPS 180-4 section 4.2.2.
+const CONSTANTS: [u32; 64] = [
+    0x42_8A_2F_98_u32,
+    0x71_37_44_91_u32,
+    0xB5_C0_FB_CF_u32,
+    0xE9_B5_DB_A5_u32,
+    0x39_56_C2_5B_u32,
+    0x59_F1_11_F1_u32,
+    0x92_3F_82_A4_u32,
+    0xAB_1C_5E_D5_u32,
+    0xD8_07_AA_98_u32,
+    0x12_83_5B_01_u32,
+    0x24_31_85_BE_u32,
+    0x55_0C_7D_C3_u32,
+    0x72_BE_5D_74_u32,
+    0x80_DE_B1_FE_u32,
+    0x9B_DC_06_A7_u32,
+    0xC1_9B_F1_74_u32,
+    0xE4_9B_69_C1_u32,
+    0xEF_BE_47_86_u32,
+    0x0F_C1_9D_C6_u32,
+    0x24_0C_A1_CC_u32,
+    0x2D_E9_2C_6F_u32,
+    0x4A_74_84_AA_u32,
+    0x5C_B0_A9_DC_u32,
+    0x76_F9_88_DA_u32,
+    0x98_3E_51_52_u32,
+    0xA8_31_C6_6D_u32,
+    0xB0_03_27_C8_u32,
+    0xBF_59_7F_C7_u32,
+    0xC6_E0_0B_F3_u32,
+    0xD5_A7_91_47_u32,
+    0x06_CA_63_51_u32,
+    0x14_29_29_67_u32,
+    0x27_B7_0A_85_u32,
+    0x2E_1B_21_38_u32,
+    0x4D_2C_6D_FC_u32,
+    0x53_38_0D_13_u32,
+    0x65_0A_73_54_u32,
+    0x76_6A_0A_BB_u32,
+    0x81_C2_C9_2E_u32,
+    0x92_72_2C_85_u32,
+    0xA2_BF_E8_A1_u32,
+    0xA8_1A_66_4B_u32,
+    0xC2_4B_8B_70_u32,
+    0xC7_6C_51_A3_u32,
+    0xD1_92_E8_19_u32,
+    0xD6_99_06_24_u32,
+    0xF4_0E_35_85_u32,
+    0x10_6A_A0_70_u32,
+    0x19_A4_C1_16_u32,
+    0x1E_37_6C_08_u32,
+    0x27_48_77_4C_u32,
+    0x34_B0_BC_B5_u32,
+    0x39_1C_0C_B3_u32,
+    0x4E_D8_AA_4A_u32,
+    0x5B_9C_CA_4F_u32,
+    0x68_2E_6F_F3_u32,
+    0x74_8F_82_EE_u32,
+    0x78_A5_63_6F_u32,
+    0x84_C8_78_14_u32,
+    0x8C_C7_02_08_u32,
+    0x90_BE_FF_FA_u32,
+    0xA4_50_6C_EB_u32,
+    0xBE_F9_A3_F7_u32,
+    0xC6_71_78_F2_u32,
+];
+
+// Initial hash values for SHA-224 as defined in FIPS 180-4 section 5.3.2.
+const SHA224_INITIAL_HASH: [u32; 8] = [
+    0xC1_05_9E_D8_u32,
+    0x36_7C_D5_07_u32,
+    0x30_70_DD_17_u32,
+    0xF7_0E_59_39_u32,
+    0xFF_C0_0B_31_u32,
+    0x68_58_15_11_u32,
+    0x64_F9_8F_A7_u32,
+    0xBE_FA_4F_A4_u32,
+];
+
+// Initial hash values for SHA-256 as defined in FIPS 180-4 section 5.3.3.
+const SHA256_INITIAL_HASH: [u32; 8] = [
+    0x6A_09_E6_67_u32,
+    0xBB_67_AE_85_u32,
+    0x3C_6E_F3_72_u32,
+    0xA5_4F_F5_3A_u32,
+    0x51_0E_52_7F_u32,
+    0x9B_05_68_8C_u32,
+    0x1F_83_D9_AB_u32,
+    0x5B_E0_CD_19_u32,
+];
+
+/// The SHA-224 secure hash.
+pub struct SHA224 {}
+
+/// The SHA-256 secure hash.
+pub struct SHA256 {}
+
+/// Shared state of the SHA-224 and SHA-256 hashers, which differ only in their initial hash
+/// values and digest length.
+#[derive(Clone)]
+struct SHA256Engine {
+    hash_values: [u32; 8],
+    buffer: BlockBuffer<64>,
+    total_data_size_bytes: u64,
+}
+
+/// Incremental SHA-224 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA224Hasher(SHA256Engine);
+
+/// Incremental SHA-256 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA256Hasher(SHA256Engine);
+
+fn compress(state: &mut [u32; 8], data_block: &[u8; 64]) {
+    // Load 512 bit data block into 16 big-endian 32 bit words of a 64 word message schedule.
+    let mut words = [0u32; 64];
+    for (word, bytes) in words.iter_mut().zip(data_block.chunks_exact(4)) {
+        *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    }
+
+    // Extend the 16 words into 64 words.
+    for i in 16..64 {
+        let sigma_0 =
+            words[i - 15].rotate_right(7) ^ words[i - 15].rotate_right(18) ^ (words[i - 15] >> 3);
+        let sigma_1 =
+            words[i - 2].rotate_right(17) ^ words[i - 2].rotate_right(19) ^ (words[i - 2] >> 10);
+        words[i] = words[i - 16]
+            .wrapping_add(sigma_0)
+            .wrapping_add(words[i - 7])
+            .wrapping_add(sigma_1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (word, constant) in words.iter().zip(CONSTANTS.iter()) {
+        let sum_1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let temp_1 = h
+            .wrapping_add(sum_1)
+            .wrapping_add(choice)
+            .wrapping_add(*constant)
+            .wrapping_add(*word);
+        let sum_0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp_2 = sum_0.wrapping_add(majority);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp_1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp_1.wrapping_add(temp_2);
+    }
+
+    for (hash_value, working_value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *hash_value = hash_value.wrapping_add(working_value);
+    }
+}
+
+impl SHA256Engine {
+    fn new(initial_hash: [u32; 8]) -> Self {
+        Self {
+            hash_values: initial_hash,
+            buffer: BlockBuffer::new(),
+            total_data_size_bytes: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_data_size_bytes = self.total_data_size_bytes.wrapping_add(data.len() as u64);
+        self.buffer
+            .update(data, |block| compress(&mut self.hash_values, block));
+    }
+
+    /// Pads the message and returns the first `digest_words` 32 bit words of the hash values.
+    fn finalize(mut self, digest_words: usize) -> Digest {
+        // Original size of message in bits is appended big-endian.
+        let total_data_size_bits = self.total_data_size_bytes.wrapping_mul(8);
+        self.buffer
+            .pad(&total_data_size_bits.to_be_bytes(), |block| {
+                compress(&mut self.hash_values, block)
+            });
+
+        let mut message_digest = Vec::with_capacity(4 * digest_words);
+        for hash_value in &self.hash_values[0..digest_words] {
+            message_digest.extend_from_slice(&hash_value.to_be_bytes());
+        }
+
+        Digest::from(message_digest)
+    }
+}
+
+impl Hasher for SHA224Hasher {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Self(SHA256Engine::new(SHA224_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(7)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA256Hasher {
+    const BLOCK_SIZE: usize = 64;
+
+    fn new() -> Self {
+        Self(SHA256Engine::new(SHA256_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(8)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hash for SHA224 {
+    type Hasher = SHA224Hasher;
+}
+
+impl CryptographicHash for SHA224 {}
+
+impl Hash for SHA256 {
+    type Hasher = SHA256Hasher;
+}
+
+impl CryptographicHash for SHA256 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_hash() {
+        let test_vec = Vec::new();
+
+        assert_eq!(
+            SHA224::hash_slice(&test_vec).to_hex(),
+            "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f"
+        );
+        assert_eq!(
+            SHA256::hash_slice(&test_vec).to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn fips_hash_suite() {
+        let mut test_vec = Vec::new();
+        test_vec.extend_from_slice("abc".as_bytes());
+
+        assert_eq!(
+            SHA224::hash_slice(&test_vec).to_hex(),
+            "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7"
+        );
+        assert_eq!(
+            SHA256::hash_slice(&test_vec).to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        test_vec.clear();
+        test_vec.extend_from_slice(
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".as_bytes(),
+        );
+
+        assert_eq!(
+            SHA224::hash_slice(&test_vec).to_hex(),
+            "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525"
+        );
+        assert_eq!(
+            SHA256::hash_slice(&test_vec).to_hex(),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        test_vec.clear();
+        test_vec.resize(1_000_000, b'a');
+
+        assert_eq!(
+            SHA224::hash_slice(&test_vec).to_hex(),
+            "20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67"
+        );
+        assert_eq!(
+            SHA256::hash_slice(&test_vec).to_hex(),
+            "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+        );
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = SHA256::hash_slice(&test_vec);
+
+        // Split the message at every chunk size up to and past the block size.
+        for chunk_size in 1..=300 {
+            let mut hasher = SHA256Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = SHA256Hasher::new();
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(
+            hasher.finalize().to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
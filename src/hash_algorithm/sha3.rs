@@ -0,0 +1,586 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! SHA-3 and the SHAKE extendable-output functions as defined in FIPS 202.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, VariableOutputHasher, digest::Digest,
+};
+
+// Round constants for the iota step as defined in FIPS 202 section 3.2.5.
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x00_00_00_00_00_00_00_01_u64,
+    0x00_00_00_00_00_00_80_82_u64,
+    0x80_00_00_00_00_00_80_8A_u64,
+    0x80_00_00_00_80_00_80_00_u64,
+    0x00_00_00_00_00_00_80_8B_u64,
+    0x00_00_00_00_80_00_00_01_u64,
+    0x80_00_00_00_80_00_80_81_u64,
+    0x80_00_00_00_00_00_80_09_u64,
+    0x00_00_00_00_00_00_00_8A_u64,
+    0x00_00_00_00_00_00_00_88_u64,
+    0x00_00_00_00_80_00_80_09_u64,
+    0x00_00_00_00_80_00_00_0A_u64,
+    0x00_00_00_00_80_00_80_8B_u64,
+    0x80_00_00_00_00_00_00_8B_u64,
+    0x80_00_00_00_00_00_80_89_u64,
+    0x80_00_00_00_00_00_80_03_u64,
+    0x80_00_00_00_00_00_80_02_u64,
+    0x80_00_00_00_00_00_00_80_u64,
+    0x00_00_00_00_00_00_80_0A_u64,
+    0x80_00_00_00_80_00_00_0A_u64,
+    0x80_00_00_00_80_00_80_81_u64,
+    0x80_00_00_00_00_00_80_80_u64,
+    0x00_00_00_00_80_00_00_01_u64,
+    0x80_00_00_00_80_00_80_08_u64,
+];
+
+// Rotation offsets of the rho step, in the order lanes are visited by the pi step.
+const ROTATIONS: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+// Order lanes are visited when combining the rho and pi steps, starting from lane 1.
+const PI_LANES: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+// Domain separation bits and the first padding bit as defined in FIPS 202 section 6.
+const SHA3_DOMAIN: u8 = 0x06;
+const SHAKE_DOMAIN: u8 = 0x1F;
+
+// Longest digest hashsum will squeeze from the extendable-output functions.
+const SHAKE_MAX_OUTPUT_BITS: usize = 65536;
+
+/// The SHA3-224 secure hash.
+pub struct SHA3_224 {}
+
+/// The SHA3-256 secure hash.
+pub struct SHA3_256 {}
+
+/// The SHA3-384 secure hash.
+pub struct SHA3_384 {}
+
+/// The SHA3-512 secure hash.
+pub struct SHA3_512 {}
+
+/// The SHAKE128 extendable-output function, producing 256 bit digests by default.
+pub struct SHAKE128 {}
+
+/// The SHAKE256 extendable-output function, producing 512 bit digests by default.
+pub struct SHAKE256 {}
+
+/// Keccak sponge shared by every SHA-3 and SHAKE hasher, which differ only in their rate, domain
+/// separation bits and output length.
+#[derive(Clone)]
+struct KeccakEngine {
+    state: [u64; 25],
+    // Number of bytes absorbed into or squeezed from the state per permutation.
+    rate: usize,
+    // Offset in bytes into the rate portion of the state of the next absorbed byte.
+    position: usize,
+    domain: u8,
+}
+
+/// Incremental SHA3-224 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA3_224Hasher(KeccakEngine);
+
+/// Incremental SHA3-256 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA3_256Hasher(KeccakEngine);
+
+/// Incremental SHA3-384 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA3_384Hasher(KeccakEngine);
+
+/// Incremental SHA3-512 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA3_512Hasher(KeccakEngine);
+
+/// Incremental SHAKE128 hasher accepting data in pieces of any length and producing digests of a
+/// chosen length.
+#[derive(Clone)]
+pub struct SHAKE128Hasher {
+    engine: KeccakEngine,
+    output_bits: usize,
+}
+
+/// Incremental SHAKE256 hasher accepting data in pieces of any length and producing digests of a
+/// chosen length.
+#[derive(Clone)]
+pub struct SHAKE256Hasher {
+    engine: KeccakEngine,
+    output_bits: usize,
+}
+
+/// The Keccak-f[1600] permutation as defined in FIPS 202 section 3.3.
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        // Theta: xor each lane with the parity of two neighbouring columns.
+        let mut parity = [0u64; 5];
+        for (x, column) in parity.iter_mut().enumerate() {
+            *column = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let theta = parity[(x + 4) % 5] ^ parity[(x + 1) % 5].rotate_left(1);
+            for y in (0..25).step_by(5) {
+                state[y + x] ^= theta;
+            }
+        }
+
+        // Rho and pi: rotate each lane and move it to its new position.
+        let mut lane = state[1];
+        for (rotation, index) in ROTATIONS.iter().zip(PI_LANES) {
+            let next_lane = state[index];
+            state[index] = lane.rotate_left(*rotation);
+            lane = next_lane;
+        }
+
+        // Chi: combine each lane with the next two lanes in its row.
+        for y in (0..25).step_by(5) {
+            let row = [
+                state[y],
+                state[y + 1],
+                state[y + 2],
+                state[y + 3],
+                state[y + 4],
+            ];
+            for x in 0..5 {
+                state[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // Iota: break the symmetry of the rounds.
+        state[0] ^= round_constant;
+    }
+}
+
+impl KeccakEngine {
+    fn new(rate: usize, domain: u8) -> Self {
+        Self {
+            state: [0u64; 25],
+            rate,
+            position: 0,
+            domain,
+        }
+    }
+
+    fn xor_byte(&mut self, offset: usize, byte: u8) {
+        // Lanes are loaded from bytes little-endian.
+        self.state[offset / 8] ^= (byte as u64) << (8 * (offset % 8));
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        // Absorb byte by byte until the position is lane aligned.
+        while !data.is_empty() && (!self.position.is_multiple_of(8) || data.len() < 8) {
+            self.xor_byte(self.position, data[0]);
+            data = &data[1..];
+            self.advance();
+        }
+
+        // Absorb whole lanes at a time.
+        let mut lanes = data.chunks_exact(8);
+        for lane in &mut lanes {
+            // Cannot panic as chunks_exact always yields 8 byte slices.
+            self.state[self.position / 8] ^= u64::from_le_bytes(lane.try_into().unwrap());
+            self.position += 7;
+            self.advance();
+        }
+
+        for &byte in lanes.remainder() {
+            self.xor_byte(self.position, byte);
+            self.advance();
+        }
+    }
+
+    /// Moves to the next byte of the rate, permuting the state once a full block is absorbed.
+    fn advance(&mut self) {
+        self.position += 1;
+
+        if self.position == self.rate {
+            keccak_f(&mut self.state);
+            self.position = 0;
+        }
+    }
+
+    /// Pads the absorbed data and squeezes `output_bytes` bytes from the sponge.
+    fn finalize(mut self, output_bytes: usize) -> Digest {
+        self.xor_byte(self.position, self.domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f(&mut self.state);
+
+        let mut message_digest = Vec::with_capacity(output_bytes);
+        loop {
+            let block = self.state.iter().flat_map(|lane| lane.to_le_bytes());
+            let bytes = self.rate.min(output_bytes - message_digest.len());
+            message_digest.extend(block.take(bytes));
+
+            if message_digest.len() == output_bytes {
+                break;
+            }
+
+            keccak_f(&mut self.state);
+        }
+
+        Digest::from(message_digest)
+    }
+}
+
+impl Hasher for SHA3_224Hasher {
+    const BLOCK_SIZE: usize = 144;
+
+    fn new() -> Self {
+        Self(KeccakEngine::new(Self::BLOCK_SIZE, SHA3_DOMAIN))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(28)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA3_256Hasher {
+    const BLOCK_SIZE: usize = 136;
+
+    fn new() -> Self {
+        Self(KeccakEngine::new(Self::BLOCK_SIZE, SHA3_DOMAIN))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(32)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA3_384Hasher {
+    const BLOCK_SIZE: usize = 104;
+
+    fn new() -> Self {
+        Self(KeccakEngine::new(Self::BLOCK_SIZE, SHA3_DOMAIN))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(48)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA3_512Hasher {
+    const BLOCK_SIZE: usize = 72;
+
+    fn new() -> Self {
+        Self(KeccakEngine::new(Self::BLOCK_SIZE, SHA3_DOMAIN))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(64)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHAKE128Hasher {
+    const BLOCK_SIZE: usize = 168;
+
+    fn new() -> Self {
+        Self::with_output_bits(Self::DEFAULT_OUTPUT_BITS)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.engine.finalize(self.output_bits / 8)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::with_output_bits(self.output_bits);
+    }
+}
+
+impl VariableOutputHasher for SHAKE128Hasher {
+    const MIN_OUTPUT_BITS: usize = 8;
+    const MAX_OUTPUT_BITS: usize = SHAKE_MAX_OUTPUT_BITS;
+    const DEFAULT_OUTPUT_BITS: usize = 256;
+
+    fn with_output_bits(output_bits: usize) -> Self {
+        Self::assert_output_bits(output_bits);
+
+        Self {
+            engine: KeccakEngine::new(Self::BLOCK_SIZE, SHAKE_DOMAIN),
+            output_bits,
+        }
+    }
+}
+
+impl Hasher for SHAKE256Hasher {
+    const BLOCK_SIZE: usize = 136;
+
+    fn new() -> Self {
+        Self::with_output_bits(Self::DEFAULT_OUTPUT_BITS)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.engine.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.engine.finalize(self.output_bits / 8)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::with_output_bits(self.output_bits);
+    }
+}
+
+impl VariableOutputHasher for SHAKE256Hasher {
+    const MIN_OUTPUT_BITS: usize = 8;
+    const MAX_OUTPUT_BITS: usize = SHAKE_MAX_OUTPUT_BITS;
+    const DEFAULT_OUTPUT_BITS: usize = 512;
+
+    fn with_output_bits(output_bits: usize) -> Self {
+        Self::assert_output_bits(output_bits);
+
+        Self {
+            engine: KeccakEngine::new(Self::BLOCK_SIZE, SHAKE_DOMAIN),
+            output_bits,
+        }
+    }
+}
+
+impl Hash for SHA3_224 {
+    type Hasher = SHA3_224Hasher;
+}
+
+impl CryptographicHash for SHA3_224 {}
+
+impl Hash for SHA3_256 {
+    type Hasher = SHA3_256Hasher;
+}
+
+impl CryptographicHash for SHA3_256 {}
+
+impl Hash for SHA3_384 {
+    type Hasher = SHA3_384Hasher;
+}
+
+impl CryptographicHash for SHA3_384 {}
+
+impl Hash for SHA3_512 {
+    type Hasher = SHA3_512Hasher;
+}
+
+impl CryptographicHash for SHA3_512 {}
+
+impl Hash for SHAKE128 {
+    type Hasher = SHAKE128Hasher;
+}
+
+impl CryptographicHash for SHAKE128 {}
+
+impl Hash for SHAKE256 {
+    type Hasher = SHAKE256Hasher;
+}
+
+impl CryptographicHash for SHAKE256 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_hash() {
+        let test_vec = Vec::new();
+
+        assert_eq!(
+            SHA3_224::hash_slice(&test_vec).to_hex(),
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7"
+        );
+        assert_eq!(
+            SHA3_256::hash_slice(&test_vec).to_hex(),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            SHA3_384::hash_slice(&test_vec).to_hex(),
+            "0c63a75b845e4f7d01107d852e4c2485c51a50aaaa94fc61995e71bbee983a2ac3713831264adb47fb6bd1e058d5f004"
+        );
+        assert_eq!(
+            SHA3_512::hash_slice(&test_vec).to_hex(),
+            "a69f73cca23a9ac5c8b567dc185a756e97c982164fe25859e0d1dcc1475c80a615b2123af1f5f94c11e3e9402c3ac558f500199d95b6d3e301758586281dcd26"
+        );
+        assert_eq!(
+            SHAKE128::hash_slice(&test_vec).to_hex(),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            SHAKE256::hash_slice(&test_vec).to_hex(),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+        );
+    }
+
+    #[test]
+    fn fips_hash_suite() {
+        let mut test_vec = Vec::new();
+        test_vec.extend_from_slice("abc".as_bytes());
+
+        assert_eq!(
+            SHA3_224::hash_slice(&test_vec).to_hex(),
+            "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf"
+        );
+        assert_eq!(
+            SHA3_256::hash_slice(&test_vec).to_hex(),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            SHA3_384::hash_slice(&test_vec).to_hex(),
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25"
+        );
+        assert_eq!(
+            SHA3_512::hash_slice(&test_vec).to_hex(),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        assert_eq!(
+            SHAKE128::hash_slice(&test_vec).to_hex(),
+            "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8"
+        );
+        assert_eq!(
+            SHAKE256::hash_slice(&test_vec).to_hex(),
+            "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4"
+        );
+
+        test_vec.clear();
+        test_vec.extend_from_slice(
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq".as_bytes(),
+        );
+
+        assert_eq!(
+            SHA3_224::hash_slice(&test_vec).to_hex(),
+            "8a24108b154ada21c9fd5574494479ba5c7e7ab76ef264ead0fcce33"
+        );
+        assert_eq!(
+            SHA3_256::hash_slice(&test_vec).to_hex(),
+            "41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376"
+        );
+        assert_eq!(
+            SHA3_384::hash_slice(&test_vec).to_hex(),
+            "991c665755eb3a4b6bbdfb75c78a492e8c56a22c5c4d7e429bfdbc32b9d4ad5aa04a1f076e62fea19eef51acd0657c22"
+        );
+        assert_eq!(
+            SHA3_512::hash_slice(&test_vec).to_hex(),
+            "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636dee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e"
+        );
+        assert_eq!(
+            SHAKE128::hash_slice(&test_vec).to_hex(),
+            "1a96182b50fb8c7e74e0a707788f55e98209b8d91fade8f32f8dd5cff7bf21f5"
+        );
+        assert_eq!(
+            SHAKE256::hash_slice(&test_vec).to_hex(),
+            "4d8c2dd2435a0128eefbb8c36f6f87133a7911e18d979ee1ae6be5d4fd2e332940d8688a4e6a59aa8060f1f9bc996c05aca3c696a8b66279dc672c740bb224ec"
+        );
+
+        // 1600 bit message of repeated 0xA3 bytes from the NIST example values.
+        test_vec.clear();
+        test_vec.resize(200, 0xA3);
+
+        assert_eq!(
+            SHA3_224::hash_slice(&test_vec).to_hex(),
+            "9376816aba503f72f96ce7eb65ac095deee3be4bf9bbc2a1cb7e11e0"
+        );
+        assert_eq!(
+            SHA3_256::hash_slice(&test_vec).to_hex(),
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787"
+        );
+        assert_eq!(
+            SHA3_384::hash_slice(&test_vec).to_hex(),
+            "1881de2ca7e41ef95dc4732b8f5f002b189cc1e42b74168ed1732649ce1dbcdd76197a31fd55ee989f2d7050dd473e8f"
+        );
+        assert_eq!(
+            SHA3_512::hash_slice(&test_vec).to_hex(),
+            "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca81b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00"
+        );
+        assert_eq!(
+            SHAKE128::hash_slice(&test_vec).to_hex(),
+            "131ab8d2b594946b9c81333f9bb6e0ce75c3b93104fa3469d3917457385da037"
+        );
+        assert_eq!(
+            SHAKE256::hash_slice(&test_vec).to_hex(),
+            "cd8a920ed141aa0407a22d59288652e9d9f1a7ee0c1e7c1ca699424da84a904d2d700caae7396ece96604440577da4f3aa22aeb8857f961c4cd8e06f0ae6610b"
+        );
+    }
+
+    #[test]
+    fn shake_output_length() {
+        let test_vec = "abc".as_bytes();
+
+        // Longer than the SHAKE128 rate so more than one block has to be squeezed.
+        assert_eq!(
+            SHAKE128::hash_slice_with_output_bits(test_vec, 1600).to_hex(),
+            "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc844c50af32acd3f2cdd066568706f509bc1bdde58295dae3f891a9a0fca5783789a41f8611214ce612394df286a62d1a2252aa94db9c538956c717dc2bed4f232a0294c857c730aa16067ac1062f1201fb0d377cfb9cde4c63599b27f3462bba4a0ed296c801f9ff7f57302bb3076ee145f97a32ae68e76ab66c48d51675bd49acc29082f5647584e6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd"
+        );
+        assert_eq!(
+            SHAKE256::hash_slice_with_output_bits(test_vec, 8).to_hex(),
+            "48"
+        );
+        assert!(SHAKE128Hasher::supports_output_bits(8));
+        assert!(!SHAKE128Hasher::supports_output_bits(0));
+        assert!(!SHAKE128Hasher::supports_output_bits(12));
+        assert!(!SHAKE128Hasher::supports_output_bits(
+            SHAKE_MAX_OUTPUT_BITS + 8
+        ));
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = SHA3_256::hash_slice(&test_vec);
+
+        // Split the message at every chunk size up to and past the block size.
+        for chunk_size in 1..=300 {
+            let mut hasher = SHA3_256Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = SHAKE128Hasher::with_output_bits(512);
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(
+            hasher.finalize(),
+            SHAKE128::hash_slice_with_output_bits("abc".as_bytes(), 512)
+        );
+    }
+}
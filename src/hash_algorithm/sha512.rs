@@ -0,0 +1,485 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! SHA-384, SHA-512, SHA-512/224 and SHA-512/256 as defined in FIPS 180-4.
+
+use crate::hash_algorithm::{
+    CryptographicHash, Hash, Hasher, block_buffer::BlockBuffer, digest::Digest,
+};
+
+// First 64 bits of the fractional parts of the cube roots of the first 80 primes as defined in
+// FIPS 180-4 section 4.2.3.
+const CONSTANTS: [u64; 80] = [
+    0x42_8A_2F_98_D7_28_AE_22_u64,
+    0x71_37_44_91_23_EF_65_CD_u64,
+    0xB5_C0_FB_CF_EC_4D_3B_2F_u64,
+    0xE9_B5_DB_A5_81_89_DB_BC_u64,
+    0x39_56_C2_5B_F3_48_B5_38_u64,
+    0x59_F1_11_F1_B6_05_D0_19_u64,
+    0x92_3F_82_A4_AF_19_4F_9B_u64,
+    0xAB_1C_5E_D5_DA_6D_81_18_u64,
+    0xD8_07_AA_98_A3_03_02_42_u64,
+    0x12_83_5B_01_45_70_6F_BE_u64,
+    0x24_31_85_BE_4E_E4_B2_8C_u64,
+    0x55_0C_7D_C3_D5_FF_B4_E2_u64,
+    0x72_BE_5D_74_F2_7B_89_6F_u64,
+    0x80_DE_B1_FE_3B_16_96_B1_u64,
+    0x9B_DC_06_A7_25_C7_12_35_u64,
+    0xC1_9B_F1_74_CF_69_26_94_u64,
+    0xE4_9B_69_C1_9E_F1_4A_D2_u64,
+    0xEF_BE_47_86_38_4F_25_E3_u64,
+    0x0F_C1_9D_C6_8B_8C_D5_B5_u64,
+    0x24_0C_A1_CC_77_AC_9C_65_u64,
+    0x2D_E9_2C_6F_59_2B_02_75_u64,
+    0x4A_74_84_AA_6E_A6_E4_83_u64,
+    0x5C_B0_A9_DC_BD_41_FB_D4_u64,
+    0x76_F9_88_DA_83_11_53_B5_u64,
+    0x98_3E_51_52_EE_66_DF_AB_u64,
+    0xA8_31_C6_6D_2D_B4_32_10_u64,
+    0xB0_03_27_C8_98_FB_21_3F_u64,
+    0xBF_59_7F_C7_BE_EF_0E_E4_u64,
+    0xC6_E0_0B_F3_3D_A8_8F_C2_u64,
+    0xD5_A7_91_47_93_0A_A7_25_u64,
+    0x06_CA_63_51_E0_03_82_6F_u64,
+    0x14_29_29_67_0A_0E_6E_70_u64,
+    0x27_B7_0A_85_46_D2_2F_FC_u64,
+    0x2E_1B_21_38_5C_26_C9_26_u64,
+    0x4D_2C_6D_FC_5A_C4_2A_ED_u64,
+    0x53_38_0D_13_9D_95_B3_DF_u64,
+    0x65_0A_73_54_8B_AF_63_DE_u64,
+    0x76_6A_0A_BB_3C_77_B2_A8_u64,
+    0x81_C2_C9_2E_47_ED_AE_E6_u64,
+    0x92_72_2C_85_14_82_35_3B_u64,
+    0xA2_BF_E8_A1_4C_F1_03_64_u64,
+    0xA8_1A_66_4B_BC_42_30_01_u64,
+    0xC2_4B_8B_70_D0_F8_97_91_u64,
+    0xC7_6C_51_A3_06_54_BE_30_u64,
+    0xD1_92_E8_19_D6_EF_52_18_u64,
+    0xD6_99_06_24_55_65_A9_10_u64,
+    0xF4_0E_35_85_57_71_20_2A_u64,
+    0x10_6A_A0_70_32_BB_D1_B8_u64,
+    0x19_A4_C1_16_B8_D2_D0_C8_u64,
+    0x1E_37_6C_08_51_41_AB_53_u64,
+    0x27_48_77_4C_DF_8E_EB_99_u64,
+    0x34_B0_BC_B5_E1_9B_48_A8_u64,
+    0x39_1C_0C_B3_C5_C9_5A_63_u64,
+    0x4E_D8_AA_4A_E3_41_8A_CB_u64,
+    0x5B_9C_CA_4F_77_63_E3_73_u64,
+    0x68_2E_6F_F3_D6_B2_B8_A3_u64,
+    0x74_8F_82_EE_5D_EF_B2_FC_u64,
+    0x78_A5_63_6F_43_17_2F_60_u64,
+    0x84_C8_78_14_A1_F0_AB_72_u64,
+    0x8C_C7_02_08_1A_64_39_EC_u64,
+    0x90_BE_FF_FA_23_63_1E_28_u64,
+    0xA4_50_6C_EB_DE_82_BD_E9_u64,
+    0xBE_F9_A3_F7_B2_C6_79_15_u64,
+    0xC6_71_78_F2_E3_72_53_2B_u64,
+    0xCA_27_3E_CE_EA_26_61_9C_u64,
+    0xD1_86_B8_C7_21_C0_C2_07_u64,
+    0xEA_DA_7D_D6_CD_E0_EB_1E_u64,
+    0xF5_7D_4F_7F_EE_6E_D1_78_u64,
+    0x06_F0_67_AA_72_17_6F_BA_u64,
+    0x0A_63_7D_C5_A2_C8_98_A6_u64,
+    0x11_3F_98_04_BE_F9_0D_AE_u64,
+    0x1B_71_0B_35_13_1C_47_1B_u64,
+    0x28_DB_77_F5_23_04_7D_84_u64,
+    0x32_CA_AB_7B_40_C7_24_93_u64,
+    0x3C_9E_BE_0A_15_C9_BE_BC_u64,
+    0x43_1D_67_C4_9C_10_0D_4C_u64,
+    0x4C_C5_D4_BE_CB_3E_42_B6_u64,
+    0x59_7F_29_9C_FC_65_7E_2A_u64,
+    0x5F_CB_6F_AB_3A_D6_FA_EC_u64,
+    0x6C_44_19_8C_4A_47_58_17_u64,
+];
+
+// Initial hash values for SHA-384 as defined in FIPS 180-4 section 5.3.4.
+const SHA384_INITIAL_HASH: [u64; 8] = [
+    0xCB_BB_9D_5D_C1_05_9E_D8_u64,
+    0x62_9A_29_2A_36_7C_D5_07_u64,
+    0x91_59_01_5A_30_70_DD_17_u64,
+    0x15_2F_EC_D8_F7_0E_59_39_u64,
+    0x67_33_26_67_FF_C0_0B_31_u64,
+    0x8E_B4_4A_87_68_58_15_11_u64,
+    0xDB_0C_2E_0D_64_F9_8F_A7_u64,
+    0x47_B5_48_1D_BE_FA_4F_A4_u64,
+];
+
+// Initial hash values for SHA-512 as defined in FIPS 180-4 section 5.3.5.
+const SHA512_INITIAL_HASH: [u64; 8] = [
+    0x6A_09_E6_67_F3_BC_C9_08_u64,
+    0xBB_67_AE_85_84_CA_A7_3B_u64,
+    0x3C_6E_F3_72_FE_94_F8_2B_u64,
+    0xA5_4F_F5_3A_5F_1D_36_F1_u64,
+    0x51_0E_52_7F_AD_E6_82_D1_u64,
+    0x9B_05_68_8C_2B_3E_6C_1F_u64,
+    0x1F_83_D9_AB_FB_41_BD_6B_u64,
+    0x5B_E0_CD_19_13_7E_21_79_u64,
+];
+
+// Initial hash values for SHA-512/224 as defined in FIPS 180-4 section 5.3.6.1.
+const SHA512_224_INITIAL_HASH: [u64; 8] = [
+    0x8C_3D_37_C8_19_54_4D_A2_u64,
+    0x73_E1_99_66_89_DC_D4_D6_u64,
+    0x1D_FA_B7_AE_32_FF_9C_82_u64,
+    0x67_9D_D5_14_58_2F_9F_CF_u64,
+    0x0F_6D_2B_69_7B_D4_4D_A8_u64,
+    0x77_E3_6F_73_04_C4_89_42_u64,
+    0x3F_9D_85_A8_6A_1D_36_C8_u64,
+    0x11_12_E6_AD_91_D6_92_A1_u64,
+];
+
+// Initial hash values for SHA-512/256 as defined in FIPS 180-4 section 5.3.6.2.
+const SHA512_256_INITIAL_HASH: [u64; 8] = [
+    0x22_31_21_94_FC_2B_F7_2C_u64,
+    0x9F_55_5F_A3_C8_4C_64_C2_u64,
+    0x23_93_B8_6B_6F_53_B1_51_u64,
+    0x96_38_77_19_59_40_EA_BD_u64,
+    0x96_28_3E_E2_A8_8E_FF_E3_u64,
+    0xBE_5E_1E_25_53_86_39_92_u64,
+    0x2B_01_99_FC_2C_85_B8_AA_u64,
+    0x0E_B7_2D_DC_81_C5_2C_A2_u64,
+];
+
+/// The SHA-384 secure hash.
+pub struct SHA384 {}
+
+/// The SHA-512 secure hash.
+pub struct SHA512 {}
+
+/// The SHA-512/224 secure hash.
+pub struct SHA512_224 {}
+
+/// The SHA-512/256 secure hash.
+pub struct SHA512_256 {}
+
+/// Shared state of the SHA-512 based hashers, which differ only in their initial hash values and
+/// digest length.
+#[derive(Clone)]
+struct SHA512Engine {
+    hash_values: [u64; 8],
+    buffer: BlockBuffer<128>,
+    total_data_size_bytes: u128,
+}
+
+/// Incremental SHA-384 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA384Hasher(SHA512Engine);
+
+/// Incremental SHA-512 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA512Hasher(SHA512Engine);
+
+/// Incremental SHA-512/224 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA512_224Hasher(SHA512Engine);
+
+/// Incremental SHA-512/256 hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SHA512_256Hasher(SHA512Engine);
+
+fn compress(state: &mut [u64; 8], data_block: &[u8; 128]) {
+    // Load 1024 bit data block into 16 big-endian 64 bit words of an 80 word message schedule.
+    let mut words = [0u64; 80];
+    for (word, bytes) in words.iter_mut().zip(data_block.chunks_exact(8)) {
+        // Cannot panic as chunks_exact always yields 8 byte slices.
+        *word = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+
+    // Extend the 16 words into 80 words.
+    for i in 16..80 {
+        let sigma_0 =
+            words[i - 15].rotate_right(1) ^ words[i - 15].rotate_right(8) ^ (words[i - 15] >> 7);
+        let sigma_1 =
+            words[i - 2].rotate_right(19) ^ words[i - 2].rotate_right(61) ^ (words[i - 2] >> 6);
+        words[i] = words[i - 16]
+            .wrapping_add(sigma_0)
+            .wrapping_add(words[i - 7])
+            .wrapping_add(sigma_1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (word, constant) in words.iter().zip(CONSTANTS.iter()) {
+        let sum_1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let choice = (e & f) ^ (!e & g);
+        let temp_1 = h
+            .wrapping_add(sum_1)
+            .wrapping_add(choice)
+            .wrapping_add(*constant)
+            .wrapping_add(*word);
+        let sum_0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let temp_2 = sum_0.wrapping_add(majority);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp_1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp_1.wrapping_add(temp_2);
+    }
+
+    for (hash_value, working_value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *hash_value = hash_value.wrapping_add(working_value);
+    }
+}
+
+impl SHA512Engine {
+    fn new(initial_hash: [u64; 8]) -> Self {
+        Self {
+            hash_values: initial_hash,
+            buffer: BlockBuffer::new(),
+            total_data_size_bytes: 0,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.total_data_size_bytes = self.total_data_size_bytes.wrapping_add(data.len() as u128);
+        self.buffer
+            .update(data, |block| compress(&mut self.hash_values, block));
+    }
+
+    /// Pads the message and returns the first `digest_bytes` bytes of the hash values.
+    fn finalize(mut self, digest_bytes: usize) -> Digest {
+        // Original size of message in bits is appended as a 128 bit big-endian integer.
+        let total_data_size_bits = self.total_data_size_bytes.wrapping_mul(8);
+        self.buffer
+            .pad(&total_data_size_bits.to_be_bytes(), |block| {
+                compress(&mut self.hash_values, block)
+            });
+
+        let message_digest = self
+            .hash_values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .take(digest_bytes)
+            .collect::<Vec<u8>>();
+
+        Digest::from(message_digest)
+    }
+}
+
+impl Hasher for SHA384Hasher {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new() -> Self {
+        Self(SHA512Engine::new(SHA384_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(48)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA512Hasher {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new() -> Self {
+        Self(SHA512Engine::new(SHA512_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(64)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA512_224Hasher {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new() -> Self {
+        Self(SHA512Engine::new(SHA512_224_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(28)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SHA512_256Hasher {
+    const BLOCK_SIZE: usize = 128;
+
+    fn new() -> Self {
+        Self(SHA512Engine::new(SHA512_256_INITIAL_HASH))
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Digest {
+        self.0.finalize(32)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hash for SHA384 {
+    type Hasher = SHA384Hasher;
+}
+
+impl CryptographicHash for SHA384 {}
+
+impl Hash for SHA512 {
+    type Hasher = SHA512Hasher;
+}
+
+impl CryptographicHash for SHA512 {}
+
+impl Hash for SHA512_224 {
+    type Hasher = SHA512_224Hasher;
+}
+
+impl CryptographicHash for SHA512_224 {}
+
+impl Hash for SHA512_256 {
+    type Hasher = SHA512_256Hasher;
+}
+
+impl CryptographicHash for SHA512_256 {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_hash() {
+        let test_vec = Vec::new();
+
+        assert_eq!(
+            SHA384::hash_slice(&test_vec).to_hex(),
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"
+        );
+        assert_eq!(
+            SHA512::hash_slice(&test_vec).to_hex(),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            SHA512_224::hash_slice(&test_vec).to_hex(),
+            "6ed0dd02806fa89e25de060c19d3ac86cabb87d6a0ddd05c333b84f4"
+        );
+        assert_eq!(
+            SHA512_256::hash_slice(&test_vec).to_hex(),
+            "c672b8d1ef56ed28ab87c3622c5114069bdd3ad7b8f9737498d0c01ecef0967a"
+        );
+    }
+
+    #[test]
+    fn fips_hash_suite() {
+        let mut test_vec = Vec::new();
+        test_vec.extend_from_slice("abc".as_bytes());
+
+        assert_eq!(
+            SHA384::hash_slice(&test_vec).to_hex(),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+        assert_eq!(
+            SHA512::hash_slice(&test_vec).to_hex(),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(
+            SHA512_224::hash_slice(&test_vec).to_hex(),
+            "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa"
+        );
+        assert_eq!(
+            SHA512_256::hash_slice(&test_vec).to_hex(),
+            "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23"
+        );
+
+        test_vec.clear();
+        test_vec.extend_from_slice(
+            "abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+                .as_bytes(),
+        );
+
+        assert_eq!(
+            SHA384::hash_slice(&test_vec).to_hex(),
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+        );
+        assert_eq!(
+            SHA512::hash_slice(&test_vec).to_hex(),
+            "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+        );
+        assert_eq!(
+            SHA512_224::hash_slice(&test_vec).to_hex(),
+            "23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9"
+        );
+        assert_eq!(
+            SHA512_256::hash_slice(&test_vec).to_hex(),
+            "3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a"
+        );
+
+        test_vec.clear();
+        test_vec.resize(1_000_000, b'a');
+
+        assert_eq!(
+            SHA384::hash_slice(&test_vec).to_hex(),
+            "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b07b8b3dc38ecc4ebae97ddd87f3d8985"
+        );
+        assert_eq!(
+            SHA512::hash_slice(&test_vec).to_hex(),
+            "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973ebde0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+        );
+        assert_eq!(
+            SHA512_224::hash_slice(&test_vec).to_hex(),
+            "37ab331d76f0d36de422bd0edeb22a28accd487b7a8453ae965dd287"
+        );
+        assert_eq!(
+            SHA512_256::hash_slice(&test_vec).to_hex(),
+            "9a59a052930187a97038cae692f30708aa6491923ef5194394dc68d56c74fb21"
+        );
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec: Vec<u8> = (0..1000_u32).map(|i| i as u8).collect();
+        let expected = SHA512::hash_slice(&test_vec);
+
+        // Split the message at every chunk size up to and past the block size.
+        for chunk_size in 1..=300 {
+            let mut hasher = SHA512Hasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                hasher.update(chunk);
+            }
+
+            assert_eq!(hasher.finalize(), expected);
+        }
+
+        let mut hasher = SHA512Hasher::new();
+        hasher.update(&test_vec);
+        hasher.reset();
+        hasher.update("abc".as_bytes());
+
+        assert_eq!(
+            hasher.finalize().to_hex(),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! The 16 bit checksums of BSD and System V sum.
+
+use crate::hash_algorithm::{Hash, Hasher, digest::Digest};
+
+/// The 16 bit rotating checksum of BSD sum.
+pub struct Bsd {}
+
+/// The 16 bit checksum of System V sum.
+pub struct Sysv {}
+
+/// Incremental BSD sum hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct BsdHasher {
+    checksum: u16,
+}
+
+/// Incremental System V sum hasher accepting data in pieces of any length.
+#[derive(Clone)]
+pub struct SysvHasher {
+    // Sum of every byte modulo 2^32, folded to 16 bits when finalized.
+    sum: u32,
+}
+
+impl Hasher for BsdHasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self { checksum: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.checksum = self.checksum.rotate_right(1).wrapping_add(*byte as u16);
+        }
+    }
+
+    /// Returns the checksum as 2 big endian bytes.
+    fn finalize(self) -> Digest {
+        Digest::from(self.checksum.to_be_bytes().to_vec())
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hasher for SysvHasher {
+    const BLOCK_SIZE: usize = 1;
+    const STREAM_BUFFER_SIZE: usize = 64 * 1024;
+
+    fn new() -> Self {
+        Self { sum: 0 }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            self.sum = self.sum.wrapping_add(*byte as u32);
+        }
+    }
+
+    /// Returns the checksum as 2 big endian bytes.
+    fn finalize(self) -> Digest {
+        let folded = (self.sum & 0xFF_FF) + (self.sum >> 16);
+        let checksum = ((folded & 0xFF_FF) + (folded >> 16)) as u16;
+
+        Digest::from(checksum.to_be_bytes().to_vec())
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Hash for Bsd {
+    type Hasher = BsdHasher;
+}
+
+impl Hash for Sysv {
+    type Hasher = SysvHasher;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum<H: Hash>(message: &[u8]) -> u16 {
+        u16::from_be_bytes(H::hash_slice(message).as_bytes().try_into().unwrap())
+    }
+
+    fn test_message() -> Vec<u8> {
+        let mut message = Vec::new();
+        for _ in 0..4 {
+            message.extend(0..=255_u8);
+        }
+        for _ in 0..50 {
+            message.extend_from_slice("hello world".as_bytes());
+        }
+
+        message
+    }
+
+    #[test]
+    fn empty_hash() {
+        assert_eq!(checksum::<Bsd>(&[]), 0);
+        assert_eq!(checksum::<Sysv>(&[]), 0);
+    }
+
+    #[test]
+    fn coreutils_hash_suite() {
+        // Values from GNU coreutils sum.
+        assert_eq!(checksum::<Bsd>("abc".as_bytes()), 16556);
+        assert_eq!(checksum::<Sysv>("abc".as_bytes()), 294);
+        assert_eq!(checksum::<Bsd>(&test_message()), 50291);
+        assert_eq!(checksum::<Sysv>(&test_message()), 55290);
+    }
+
+    #[test]
+    fn incremental_hash() {
+        let test_vec = test_message();
+
+        for chunk_size in 1..=100 {
+            let mut bsd_hasher = BsdHasher::new();
+            let mut sysv_hasher = SysvHasher::new();
+            for chunk in test_vec.chunks(chunk_size) {
+                bsd_hasher.update(chunk);
+                sysv_hasher.update(chunk);
+            }
+
+            assert_eq!(bsd_hasher.finalize(), Bsd::hash_slice(&test_vec));
+            assert_eq!(sysv_hasher.finalize(), Sysv::hash_slice(&test_vec));
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! Message digest, checksum and HMAC algorithms written in plain Rust with no dependencies.
+//!
+//! Every algorithm is a unit struct implementing [`Hash`](hash_algorithm::Hash), which hashes
+//! slices and streams in one call, while its [`Hasher`](hash_algorithm::Hasher) accepts data in
+//! pieces of any length. Algorithms with a caller chosen digest length also implement
+//! [`VariableOutputHasher`](hash_algorithm::VariableOutputHasher), and
+//! [`Hmac`](hash_algorithm::hmac::Hmac) builds an HMAC on top of any of them implementing
+//! [`CryptographicHash`](hash_algorithm::CryptographicHash).
+//!
+//! ```
+//! # #[cfg(feature = "sha2")]
+//! # {
+//! use hashsum::hash_algorithm::{Hash, Hasher, hmac::Hmac, sha256::SHA256};
+//!
+//! let digest = SHA256::hash_slice(b"abc");
+//! assert_eq!(
+//!     digest.to_hex(),
+//!     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+//! );
+//!
+//! let mut hasher = <SHA256 as Hash>::Hasher::new();
+//! hasher.update(b"a");
+//! hasher.update(b"bc");
+//! assert_eq!(hasher.finalize(), digest);
+//!
+//! let mac = Hmac::<SHA256>::hash_slice(b"key", b"abc");
+//! assert_eq!(mac.len(), 32);
+//! # }
+//! ```
+//!
+//! # Features
+//!
+//! Each family of algorithms sits behind a Cargo feature, all of which are enabled by default:
+//!
+//! - `md5`: [`md5`](hash_algorithm::md5)
+//! - `sha1`: [`sha1`](hash_algorithm::sha1)
+//! - `sha2`: [`sha256`](hash_algorithm::sha256) and [`sha512`](hash_algorithm::sha512)
+//! - `sha3`: [`sha3`](hash_algorithm::sha3), including SHAKE
+//! - `blake2`: [`blake2b`](hash_algorithm::blake2b) and [`blake2s`](hash_algorithm::blake2s)
+//! - `blake3`: [`blake3`](hash_algorithm::blake3)
+//! - `crc`: [`crc`](hash_algorithm::crc)
+//! - `sum`: [`sum`](hash_algorithm::sum)
+//!
+//! The `hashsum` binary needs every feature.
+//!
+//! The [`tree`] module serialises a whole directory tree into a stream, so any of the algorithms
+//! can produce a single digest of the tree.
+
+#![warn(missing_docs)]
+
+pub mod hash_algorithm;
+pub mod tree;
@@ -1,16 +1,54 @@
 // SPDX-License-Identifier: GPL-3.0-or-later
 // SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
 
-use crate::hash_algorithm::{Hash, md5::MD5, sha1::SHA1};
+use hashsum::hash_algorithm::{
+    Hash, Hasher, VariableOutputHasher,
+    blake2b::{BLAKE2b, BLAKE2bHasher},
+    blake2s::{BLAKE2s, BLAKE2sHasher},
+    blake3::{BLAKE3, BLAKE3Hasher},
+    crc::{Crc, Crc32, Crc32c, Crc64},
+    digest::Digest,
+    hmac::Hmac,
+    md5::MD5,
+    sha1::SHA1,
+    sha3::{
+        SHA3_224, SHA3_256, SHA3_384, SHA3_512, SHAKE128, SHAKE128Hasher, SHAKE256, SHAKE256Hasher,
+    },
+    sha256::{SHA224, SHA256},
+    sha512::{SHA384, SHA512, SHA512_224, SHA512_256},
+    sum::{Bsd, Sysv},
+};
+use hashsum::tree::TreeReader;
 use std::{
+    collections::BTreeMap,
     env::{self},
+    ffi::{OsStr, OsString},
     fmt::Display,
-    fs::File,
-    io::{self, BufReader},
+    fs::{self, File, Metadata},
+    io::{self, BufReader, ErrorKind, Read, Write},
+    path::Path,
     process,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
 };
 
-mod hash_algorithm;
+mod check;
+mod fan_out;
+mod filename;
+mod glob;
+mod mmap;
+mod options;
+mod walk;
+
+use check::CheckOptions;
+use fan_out::fan_out;
+use glob::Pattern;
+use mmap::MappedReader;
+use options::{Arg, OptionSpec, ParseError, Parser};
+use walk::{WalkError, WalkOptions};
 
 #[allow(dead_code)]
 const VERSION_MAJOR: usize = 0;
@@ -20,46 +58,299 @@ const VERSION_MIN: usize = 1;
 const VERSION_PATCH: usize = 0;
 const KIB: usize = 1024;
 const MIB: usize = 1024 * KIB;
-#[allow(dead_code)]
 const GIB: usize = 1024 * MIB;
-const FILE_BUFFER: usize = 512 * MIB;
+// Size of reads from files and stdin unless set with '--buffer-size', as used by coreutils.
+const DEFAULT_BUFFER_SIZE: usize = 128 * KIB;
+// Smallest buffer used for a small file unless '--buffer-size' is smaller, which is the default of
+// BufReader.
+const MIN_BUFFER_SIZE: usize = 8 * KIB;
 const HELP_STRING: &str = "Usage: hashsum [OPTION]... [FILE]...
 With no FILE, or when FILE is -, read standard input.
 
 Mandatory arguments to long options are mandatory for short options too.
     -a, --algorithm=DIGEST    Select the digest type to use. See DIGEST below for more info.
+                                Several may be separated by commas or given by repeating the
+                                option, printing a line for each from a single read of the file.
     -b, --base64              Emit base64-encoded digests instead of the default hexadecimal.
+        --buffer-size=SIZE    Read files SIZE bytes at a time, with an optional K, M or G suffix
+                                for units of 1024. Larger regular files are memory mapped where
+                                supported. Defaults to 128K.
+    -c, --check               Read checksums from the FILEs and check them.
+    -L, --dereference         Follow symbolic links found in directories when recursing.
+        --derive-key=CONTEXT  Use the blake3 key derivation mode with the context string CONTEXT.
+        --exclude=GLOB        Skip files and directories matching GLOB when recursing.
+        --files0-from=F       Read the FILEs from the NUL-terminated names in file F, or from
+                                standard input if F is -.
+        --hmac-key-file=FILE  Compute an HMAC of each file using the key read from FILE.
+        --include=GLOB        Only hash files matching GLOB when recursing.
+    -j, --jobs=N              Hash up to N files at once, on at most N threads between them.
+                                Checksums are still printed in the order of the FILEs. Defaults
+                                to the number of CPUs, as does 0.
+        --key-file=FILE       Use the blake3 keyed hash mode with the 32 byte key read from FILE.
+    -l, --length=BITS         Digest length in bits for blake2b, blake2s, blake3, shake128 and
+                                shake256. Must be a multiple of 8. 0 selects the default length.
+    -x, --one-file-system     Skip directories on other file systems when recursing.
+    -r, --recursive           Hash the regular files in directory FILEs and their subdirectories.
+        --tree-digest         Hash each FILE as a whole tree, including names, file contents,
+                                executable bits and symbolic link targets.
     -u, --untagged            Create a reversed style checksum, without digest type.
                                 Default is a BSD-style checksum.
+    -z, --zero                End each output line with NUL rather than newline, and don't
+                                escape file names.
     -h, --help                Display this help and exit.
     -V, --version             Output version information and exit.
 
+The following five options are only meaningful when checking checksums:
+        --ignore-missing      Don't fail or report status for missing files.
+        --quiet               Don't print OK for each successfully verified file.
+        --status              Don't output anything, the exit status shows success.
+        --strict              Exit non-zero for improperly formatted checksum lines.
+    -w, --warn                Warn about improperly formatted checksum lines.
+
 DIGEST determines the digest algorithm and default output format:
+    sysv        (equivalent to sum -s)
+    bsd         (equivalent to sum -r)
+    crc         (equivalent to cksum, the default)
+    crc32b      (CRC-32 as used by zlib, gzip and zip)
+    crc32c      (CRC-32C as used by iSCSI and ext4)
+    crc64       (CRC-64/XZ as used by xz)
     md5
-    sha1";
+    sha1
+    sha224
+    sha256
+    sha384
+    sha512
+    sha512-224
+    sha512-256
+    sha3-224
+    sha3-256
+    sha3-384
+    sha3-512
+    shake128    (default length 256 bits)
+    shake256    (default length 512 bits)
+    blake2b     (default length 512 bits)
+    blake2s     (default length 256 bits)
+    blake3      (default length 256 bits)
+
+When recursing, the files of each directory are hashed in byte order of their names and printed
+as the directory FILE followed by their path within it. GLOB is a shell wildcard pattern where
+'**' also matches across directories. A GLOB containing '/' is matched against the path within
+the directory FILE, otherwise against the file name. --include and --exclude may be repeated.
+
+--tree-digest hashes a canonical serialisation of each FILE, which must not be '-'. Symbolic
+links are hashed as their targets rather than followed, and --check verifies the listing the
+same way.";
+// Options in the order they are listed by '--help'.
+const OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        long: "algorithm",
+        short: Some('a'),
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "base64",
+        short: Some('b'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "buffer-size",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "check",
+        short: Some('c'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "dereference",
+        short: Some('L'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "derive-key",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "exclude",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "files0-from",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "hmac-key-file",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "include",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "jobs",
+        short: Some('j'),
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "key-file",
+        short: None,
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "length",
+        short: Some('l'),
+        takes_argument: true,
+    },
+    OptionSpec {
+        long: "one-file-system",
+        short: Some('x'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "recursive",
+        short: Some('r'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "tree-digest",
+        short: None,
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "untagged",
+        short: Some('u'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "zero",
+        short: Some('z'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "help",
+        short: Some('h'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "version",
+        short: Some('V'),
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "ignore-missing",
+        short: None,
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "quiet",
+        short: None,
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "status",
+        short: None,
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "strict",
+        short: None,
+        takes_argument: false,
+    },
+    OptionSpec {
+        long: "warn",
+        short: Some('w'),
+        takes_argument: false,
+    },
+];
 const HELP_INFO_STRING: &str = "Try \'hashsum --help\' for more information.";
 const HELP_ALGORITHM_ARGUMENTS: &str = "Valid arguments are:
+    - \'sysv\'
+    - \'bsd\'
+    - \'crc\'
+    - \'crc32b\'
+    - \'crc32c\'
+    - \'crc64\'
     - \'md5\'
-    - \'sha1\'";
+    - \'sha1\'
+    - \'sha224\'
+    - \'sha256\'
+    - \'sha384\'
+    - \'sha512\'
+    - \'sha512-224\'
+    - \'sha512-256\'
+    - \'sha3-224\'
+    - \'sha3-256\'
+    - \'sha3-384\'
+    - \'sha3-512\'
+    - \'shake128\'
+    - \'shake256\'
+    - \'blake2b\'
+    - \'blake2s\'
+    - \'blake3\'";
 
-fn print_help_unrecognised_option(arg: impl Display) {
-    println!("hashsum: unrecognised option \'{arg}\'\n{HELP_INFO_STRING}");
-    process::exit(1);
+/// Returns the message of `error`, leaving out the '(os error N)' that Rust adds to it as
+/// coreutils does.
+fn error_reason(error: impl Display) -> String {
+    let mut reason = error.to_string();
+    if let Some(index) = reason.rfind(" (os error ")
+        && reason.ends_with(')')
+    {
+        reason.truncate(index);
+    }
+    reason
+}
+
+/// Prints an error reading `path` as 'hashsum: PATH: reason'.
+fn print_error(path: impl Display, error: impl Display) {
+    eprintln!("hashsum: {path}: {}", error_reason(error));
+}
+
+/// Prints an error hashing `argument`. Errors serialising a tree already name the path within it
+/// they occurred at, so only errors reading a file are prefixed with the argument.
+fn print_hash_error(state: &State, argument: &OsStr, error: io::Error) {
+    if state.tree_digest {
+        eprintln!("hashsum: {}", error_reason(error));
+    } else {
+        print_error(argument.display(), error);
+    }
+}
+
+/// Writes `bytes` to stdout, which unlike `print!` allows file names that aren't valid Unicode.
+/// Exits if stdout can't be written.
+fn print_bytes(bytes: &[u8]) {
+    let mut stdout = io::stdout().lock();
+    let mut result = stdout.write_all(bytes);
+    // Records ending in NUL with '-z' aren't flushed by the line buffering, so they're flushed here
+    // to reach a reader such as `xargs -0` straight away. Newline records are left to the buffer.
+    if result.is_ok() && bytes.last() != Some(&b'\n') {
+        result = stdout.flush();
+    }
+    if let Err(e) = result {
+        print_error("standard output", e);
+        process::exit(1);
+    }
 }
 
-fn print_help_invalid_option(arg: impl Display) {
-    println!("hashsum: invalid option -- \'{arg}\'\n{HELP_INFO_STRING}");
+fn print_help_parse_error(error: ParseError) {
+    eprintln!("hashsum: {error}\n{HELP_INFO_STRING}");
     process::exit(1);
 }
 
 fn print_help_invalid_argument(arg: impl Display, option: impl Display, valid_arguments: &str) {
-    println!(
+    eprintln!(
         "hashsum: invalid argument \'{arg}\' for \'{option}\'\n{valid_arguments}\n{HELP_INFO_STRING}"
     );
     process::exit(1);
 }
-fn print_help_option_requires_argument(arg: impl Display) {
-    println!("hashsum: option requires an argument -- '{arg}'\n{HELP_INFO_STRING}");
+
+fn print_help_invalid_length(length: impl Display, reason: impl Display) {
+    eprintln!("hashsum: invalid length: \'{length}\'\nhashsum: {reason}\n{HELP_INFO_STRING}");
     process::exit(1);
 }
 
@@ -78,231 +369,1309 @@ General Public Licence for details."
     process::exit(0);
 }
 
+/// An entry of a '--files0-from' list that can't name a file, reported in place of the file as
+/// 'LIST:N: reason'.
+struct InvalidEntry {
+    pub location: String,
+    pub reason: &'static str,
+}
+
+/// A file named on the command line or in a '--files0-from' list, or the entry of the list that
+/// couldn't name one.
+type Operand = Result<OsString, InvalidEntry>;
+
 struct State {
-    pub arguments: Vec<String>,
+    pub arguments: Vec<Operand>,
+    /// True if the arguments were read from '--files0-from', so having none means there are no
+    /// files rather than that standard input is read.
+    pub files_from_list: bool,
     pub base64: bool,
     pub output_untagged: bool,
-    pub algorithm: Algorithm,
+    /// True if output lines end with NUL rather than newline, from '--zero'.
+    pub zero: bool,
+    pub check: bool,
+    pub check_options: CheckOptions,
+    /// Algorithms selected with '--algorithm' in the order given, each with the digest length in
+    /// bits selected with '--length', or None for its default length.
+    pub algorithms: Vec<(Algorithm, Option<usize>)>,
+    pub blake3_mode: Blake3Mode,
+    /// Key read from '--hmac-key-file', or None if digests aren't HMACs.
+    pub hmac_key: Option<Vec<u8>>,
+    /// True if directory arguments are walked with '--recursive'.
+    pub recursive: bool,
+    pub walk_options: WalkOptions,
+    /// True if each argument is hashed as a whole tree with '--tree-digest'.
+    pub tree_digest: bool,
+    /// Number of files hashed at once, from '--jobs'.
+    pub jobs: usize,
+    /// Size of reads from files, from '--buffer-size'.
+    pub buffer_size: usize,
+}
+
+/// BLAKE3 mode selected with '--key-file' or '--derive-key'.
+#[derive(Clone, Debug, PartialEq)]
+enum Blake3Mode {
+    Hash,
+    KeyedHash([u8; 32]),
+    DeriveKey(String),
+}
+
+/// BLAKE3 mode a digest was made in as recorded in its tag, without the key or context needed to
+/// check it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Blake3Kind {
+    Hash,
+    KeyedHash,
+    DeriveKey,
+}
+
+impl Blake3Mode {
+    fn kind(&self) -> Blake3Kind {
+        match self {
+            Blake3Mode::Hash => Blake3Kind::Hash,
+            Blake3Mode::KeyedHash(_) => Blake3Kind::KeyedHash,
+            Blake3Mode::DeriveKey(_) => Blake3Kind::DeriveKey,
+        }
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 enum Algorithm {
+    Sysv,
+    Bsd,
+    Crc,
+    Crc32b,
+    Crc32c,
+    Crc64,
     MD5,
     SHA1,
+    SHA224,
+    SHA256,
+    SHA384,
+    SHA512,
+    SHA512_224,
+    SHA512_256,
+    SHA3_224,
+    SHA3_256,
+    SHA3_384,
+    SHA3_512,
+    SHAKE128,
+    SHAKE256,
+    BLAKE2b,
+    BLAKE2s,
+    BLAKE3,
+}
+
+impl Algorithm {
+    /// Returns the algorithm matching a DIGEST argument passed to '-a'.
+    fn from_argument(argument: &str) -> Option<Self> {
+        match argument {
+            "sysv" => Some(Algorithm::Sysv),
+            "bsd" => Some(Algorithm::Bsd),
+            "crc" => Some(Algorithm::Crc),
+            "crc32b" => Some(Algorithm::Crc32b),
+            "crc32c" => Some(Algorithm::Crc32c),
+            "crc64" => Some(Algorithm::Crc64),
+            "md5" => Some(Algorithm::MD5),
+            "sha1" => Some(Algorithm::SHA1),
+            "sha224" => Some(Algorithm::SHA224),
+            "sha256" => Some(Algorithm::SHA256),
+            "sha384" => Some(Algorithm::SHA384),
+            "sha512" => Some(Algorithm::SHA512),
+            "sha512-224" => Some(Algorithm::SHA512_224),
+            "sha512-256" => Some(Algorithm::SHA512_256),
+            "sha3-224" => Some(Algorithm::SHA3_224),
+            "sha3-256" => Some(Algorithm::SHA3_256),
+            "sha3-384" => Some(Algorithm::SHA3_384),
+            "sha3-512" => Some(Algorithm::SHA3_512),
+            "shake128" => Some(Algorithm::SHAKE128),
+            "shake256" => Some(Algorithm::SHAKE256),
+            "blake2b" => Some(Algorithm::BLAKE2b),
+            "blake2s" => Some(Algorithm::BLAKE2s),
+            "blake3" => Some(Algorithm::BLAKE3),
+            _ => None,
+        }
+    }
+
+    /// Returns the tag identifying this algorithm in BSD-style checksum lines. The legacy
+    /// checksums are never tagged, so their tag only names them in messages.
+    fn tag(&self) -> &'static str {
+        match self {
+            Algorithm::Sysv => "SYSV",
+            Algorithm::Bsd => "BSD",
+            Algorithm::Crc => "CRC",
+            Algorithm::Crc32b => "CRC32B",
+            Algorithm::Crc32c => "CRC32C",
+            Algorithm::Crc64 => "CRC64",
+            Algorithm::MD5 => "MD5",
+            Algorithm::SHA1 => "SHA1",
+            Algorithm::SHA224 => "SHA224",
+            Algorithm::SHA256 => "SHA256",
+            Algorithm::SHA384 => "SHA384",
+            Algorithm::SHA512 => "SHA512",
+            Algorithm::SHA512_224 => "SHA512/224",
+            Algorithm::SHA512_256 => "SHA512/256",
+            Algorithm::SHA3_224 => "SHA3-224",
+            Algorithm::SHA3_256 => "SHA3-256",
+            Algorithm::SHA3_384 => "SHA3-384",
+            Algorithm::SHA3_512 => "SHA3-512",
+            Algorithm::SHAKE128 => "SHAKE128",
+            Algorithm::SHAKE256 => "SHAKE256",
+            Algorithm::BLAKE2b => "BLAKE2b",
+            Algorithm::BLAKE2s => "BLAKE2s",
+            Algorithm::BLAKE3 => "BLAKE3",
+        }
+    }
+
+    /// Returns the tag for a digest of `output_bits` bits made in the BLAKE3 mode `blake3`,
+    /// appending the length as in 'SHAKE128-512' when it differs from the default. Keyed and
+    /// derived key BLAKE3 digests are tagged 'BLAKE3-KEYED' and 'BLAKE3-DERIVE', so they can't be
+    /// mistaken for plain hashes.
+    fn tag_with_length(&self, output_bits: Option<usize>, blake3: Blake3Kind) -> String {
+        let tag = match (self, blake3) {
+            (Algorithm::BLAKE3, Blake3Kind::KeyedHash) => "BLAKE3-KEYED",
+            (Algorithm::BLAKE3, Blake3Kind::DeriveKey) => "BLAKE3-DERIVE",
+            _ => self.tag(),
+        };
+
+        match output_bits {
+            Some(bits) if Some(bits) != self.default_output_bits() => format!("{tag}-{bits}"),
+            _ => tag.to_string(),
+        }
+    }
+
+    /// Returns the algorithm, digest length in bits and BLAKE3 mode matching a tag produced by
+    /// `tag_with_length`.
+    fn from_tag_with_length(tag: &str) -> Option<(Self, Option<usize>, Blake3Kind)> {
+        if let Some((algorithm, blake3)) = Self::from_tag(tag) {
+            return Some((algorithm, None, blake3));
+        }
+
+        let (tag, bits) = tag.rsplit_once('-')?;
+        let (algorithm, blake3) = Self::from_tag(tag)?;
+        let bits = bits.parse().ok()?;
+        if !algorithm.supports_output_bits(bits) {
+            return None;
+        }
+
+        Some((algorithm, Some(bits), blake3))
+    }
+
+    /// Returns the algorithm and BLAKE3 mode matching the tag used in BSD-style checksum lines.
+    fn from_tag(tag: &str) -> Option<(Self, Blake3Kind)> {
+        let blake3 = match tag {
+            "BLAKE3-KEYED" => Blake3Kind::KeyedHash,
+            "BLAKE3-DERIVE" => Blake3Kind::DeriveKey,
+            _ => Blake3Kind::Hash,
+        };
+        if blake3 != Blake3Kind::Hash {
+            return Some((Algorithm::BLAKE3, blake3));
+        }
+
+        let algorithm = match tag {
+            "CRC32C" => Some(Algorithm::Crc32c),
+            "CRC64" => Some(Algorithm::Crc64),
+            "MD5" => Some(Algorithm::MD5),
+            "SHA1" => Some(Algorithm::SHA1),
+            "SHA224" => Some(Algorithm::SHA224),
+            "SHA256" => Some(Algorithm::SHA256),
+            "SHA384" => Some(Algorithm::SHA384),
+            "SHA512" => Some(Algorithm::SHA512),
+            "SHA512/224" => Some(Algorithm::SHA512_224),
+            "SHA512/256" => Some(Algorithm::SHA512_256),
+            "SHA3-224" => Some(Algorithm::SHA3_224),
+            "SHA3-256" => Some(Algorithm::SHA3_256),
+            "SHA3-384" => Some(Algorithm::SHA3_384),
+            "SHA3-512" => Some(Algorithm::SHA3_512),
+            "SHAKE128" => Some(Algorithm::SHAKE128),
+            "SHAKE256" => Some(Algorithm::SHAKE256),
+            "BLAKE2b" => Some(Algorithm::BLAKE2b),
+            "BLAKE2s" => Some(Algorithm::BLAKE2s),
+            "BLAKE3" => Some(Algorithm::BLAKE3),
+            _ => None,
+        }?;
+
+        Some((algorithm, blake3))
+    }
+
+    /// Returns true for the sum and cksum checksums, which have their own output formats and
+    /// can't be checked.
+    fn is_legacy_checksum(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::Sysv | Algorithm::Bsd | Algorithm::Crc | Algorithm::Crc32b
+        )
+    }
+
+    /// Returns false for the non-cryptographic checksums, which can't be used in an HMAC.
+    fn supports_hmac(&self) -> bool {
+        !matches!(
+            self,
+            Algorithm::Sysv
+                | Algorithm::Bsd
+                | Algorithm::Crc
+                | Algorithm::Crc32b
+                | Algorithm::Crc32c
+                | Algorithm::Crc64
+        )
+    }
+
+    /// Default digest length in bits for algorithms accepting '--length', or None if the
+    /// digest length is fixed.
+    fn default_output_bits(&self) -> Option<usize> {
+        match self {
+            Algorithm::SHAKE128 => Some(SHAKE128Hasher::DEFAULT_OUTPUT_BITS),
+            Algorithm::SHAKE256 => Some(SHAKE256Hasher::DEFAULT_OUTPUT_BITS),
+            Algorithm::BLAKE2b => Some(BLAKE2bHasher::DEFAULT_OUTPUT_BITS),
+            Algorithm::BLAKE2s => Some(BLAKE2sHasher::DEFAULT_OUTPUT_BITS),
+            Algorithm::BLAKE3 => Some(BLAKE3Hasher::DEFAULT_OUTPUT_BITS),
+            _ => None,
+        }
+    }
+
+    /// Longest digest length in bits for algorithms accepting '--length'.
+    fn max_output_bits(&self) -> Option<usize> {
+        match self {
+            Algorithm::SHAKE128 => Some(SHAKE128Hasher::MAX_OUTPUT_BITS),
+            Algorithm::SHAKE256 => Some(SHAKE256Hasher::MAX_OUTPUT_BITS),
+            Algorithm::BLAKE2b => Some(BLAKE2bHasher::MAX_OUTPUT_BITS),
+            Algorithm::BLAKE2s => Some(BLAKE2sHasher::MAX_OUTPUT_BITS),
+            Algorithm::BLAKE3 => Some(BLAKE3Hasher::MAX_OUTPUT_BITS),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this algorithm can produce a digest of `output_bits` bits.
+    fn supports_output_bits(&self, output_bits: usize) -> bool {
+        match self {
+            Algorithm::SHAKE128 => SHAKE128Hasher::supports_output_bits(output_bits),
+            Algorithm::SHAKE256 => SHAKE256Hasher::supports_output_bits(output_bits),
+            Algorithm::BLAKE2b => BLAKE2bHasher::supports_output_bits(output_bits),
+            Algorithm::BLAKE2s => BLAKE2sHasher::supports_output_bits(output_bits),
+            Algorithm::BLAKE3 => BLAKE3Hasher::supports_output_bits(output_bits),
+            _ => false,
+        }
+    }
+
+    /// Length in bytes of a digest produced by this algorithm, using `output_bits` bits if set.
+    fn digest_length(&self, output_bits: Option<usize>) -> usize {
+        if let Some(bits) = output_bits.or(self.default_output_bits()) {
+            return bits / 8;
+        }
+
+        match self {
+            Algorithm::Sysv => 2,
+            Algorithm::Bsd => 2,
+            Algorithm::Crc => 4,
+            Algorithm::Crc32b => 4,
+            Algorithm::Crc32c => 4,
+            Algorithm::Crc64 => 8,
+            Algorithm::MD5 => 16,
+            Algorithm::SHA1 => 20,
+            Algorithm::SHA224 => 28,
+            Algorithm::SHA256 => 32,
+            Algorithm::SHA384 => 48,
+            Algorithm::SHA512 => 64,
+            Algorithm::SHA512_224 => 28,
+            Algorithm::SHA512_256 => 32,
+            Algorithm::SHA3_224 => 28,
+            Algorithm::SHA3_256 => 32,
+            Algorithm::SHA3_384 => 48,
+            Algorithm::SHA3_512 => 64,
+            // Covered by the default output length above.
+            Algorithm::SHAKE128
+            | Algorithm::SHAKE256
+            | Algorithm::BLAKE2b
+            | Algorithm::BLAKE2s
+            | Algorithm::BLAKE3 => unreachable!(),
+        }
+    }
+
+    /// Hashes `message`, producing a digest of `output_bits` bits if set. The length must
+    /// already be supported by the algorithm, while `blake3_mode` and the number of `threads`
+    /// BLAKE3 may hash on are ignored by other algorithms.
+    fn hash_stream(
+        &self,
+        message: impl Read,
+        output_bits: Option<usize>,
+        blake3_mode: &Blake3Mode,
+        threads: usize,
+    ) -> io::Result<Digest> {
+        match self {
+            Algorithm::Sysv => Sysv::hash_stream(message),
+            Algorithm::Bsd => Bsd::hash_stream(message),
+            Algorithm::Crc => Crc::hash_stream(message),
+            Algorithm::Crc32b => Crc32::hash_stream(message),
+            Algorithm::Crc32c => Crc32c::hash_stream(message),
+            Algorithm::Crc64 => Crc64::hash_stream(message),
+            Algorithm::MD5 => MD5::hash_stream(message),
+            Algorithm::SHA1 => SHA1::hash_stream(message),
+            Algorithm::SHA224 => SHA224::hash_stream(message),
+            Algorithm::SHA256 => SHA256::hash_stream(message),
+            Algorithm::SHA384 => SHA384::hash_stream(message),
+            Algorithm::SHA512 => SHA512::hash_stream(message),
+            Algorithm::SHA512_224 => SHA512_224::hash_stream(message),
+            Algorithm::SHA512_256 => SHA512_256::hash_stream(message),
+            Algorithm::SHA3_224 => SHA3_224::hash_stream(message),
+            Algorithm::SHA3_256 => SHA3_256::hash_stream(message),
+            Algorithm::SHA3_384 => SHA3_384::hash_stream(message),
+            Algorithm::SHA3_512 => SHA3_512::hash_stream(message),
+            Algorithm::SHAKE128 => match output_bits {
+                Some(bits) => SHAKE128::hash_stream_with_output_bits(message, bits),
+                None => SHAKE128::hash_stream(message),
+            },
+            Algorithm::SHAKE256 => match output_bits {
+                Some(bits) => SHAKE256::hash_stream_with_output_bits(message, bits),
+                None => SHAKE256::hash_stream(message),
+            },
+            Algorithm::BLAKE2b => match output_bits {
+                Some(bits) => BLAKE2b::hash_stream_with_output_bits(message, bits),
+                None => BLAKE2b::hash_stream(message),
+            },
+            Algorithm::BLAKE2s => match output_bits {
+                Some(bits) => BLAKE2s::hash_stream_with_output_bits(message, bits),
+                None => BLAKE2s::hash_stream(message),
+            },
+            Algorithm::BLAKE3 => {
+                let output_bits = output_bits.unwrap_or(BLAKE3Hasher::DEFAULT_OUTPUT_BITS);
+                let hasher = match blake3_mode {
+                    Blake3Mode::Hash => BLAKE3Hasher::with_output_bits(output_bits),
+                    Blake3Mode::KeyedHash(key) => BLAKE3Hasher::new_keyed(key, output_bits),
+                    Blake3Mode::DeriveKey(context) => {
+                        BLAKE3Hasher::new_derive_key(context, output_bits)
+                    }
+                };
+
+                hasher.with_threads(threads).finalize_stream(message)
+            }
+        }
+    }
+
+    /// Computes the HMAC of `message` using `key`, on top of digests of `output_bits` bits if
+    /// set. The algorithm must support HMACs and the length must already be supported. BLAKE3
+    /// hashes on at most `threads` threads.
+    fn hmac_stream(
+        &self,
+        message: impl Read,
+        key: &[u8],
+        output_bits: Option<usize>,
+        threads: usize,
+    ) -> io::Result<Digest> {
+        match self {
+            Algorithm::MD5 => Hmac::<MD5>::hash_stream(key, message),
+            Algorithm::SHA1 => Hmac::<SHA1>::hash_stream(key, message),
+            Algorithm::SHA224 => Hmac::<SHA224>::hash_stream(key, message),
+            Algorithm::SHA256 => Hmac::<SHA256>::hash_stream(key, message),
+            Algorithm::SHA384 => Hmac::<SHA384>::hash_stream(key, message),
+            Algorithm::SHA512 => Hmac::<SHA512>::hash_stream(key, message),
+            Algorithm::SHA512_224 => Hmac::<SHA512_224>::hash_stream(key, message),
+            Algorithm::SHA512_256 => Hmac::<SHA512_256>::hash_stream(key, message),
+            Algorithm::SHA3_224 => Hmac::<SHA3_224>::hash_stream(key, message),
+            Algorithm::SHA3_256 => Hmac::<SHA3_256>::hash_stream(key, message),
+            Algorithm::SHA3_384 => Hmac::<SHA3_384>::hash_stream(key, message),
+            Algorithm::SHA3_512 => Hmac::<SHA3_512>::hash_stream(key, message),
+            Algorithm::SHAKE128 => match output_bits {
+                Some(bits) => Hmac::<SHAKE128>::hash_stream_with_output_bits(key, message, bits),
+                None => Hmac::<SHAKE128>::hash_stream(key, message),
+            },
+            Algorithm::SHAKE256 => match output_bits {
+                Some(bits) => Hmac::<SHAKE256>::hash_stream_with_output_bits(key, message, bits),
+                None => Hmac::<SHAKE256>::hash_stream(key, message),
+            },
+            Algorithm::BLAKE2b => match output_bits {
+                Some(bits) => Hmac::<BLAKE2b>::hash_stream_with_output_bits(key, message, bits),
+                None => Hmac::<BLAKE2b>::hash_stream(key, message),
+            },
+            Algorithm::BLAKE2s => match output_bits {
+                Some(bits) => Hmac::<BLAKE2s>::hash_stream_with_output_bits(key, message, bits),
+                None => Hmac::<BLAKE2s>::hash_stream(key, message),
+            },
+            Algorithm::BLAKE3 => {
+                let output_bits = output_bits.unwrap_or(BLAKE3Hasher::DEFAULT_OUTPUT_BITS);
+                let new_hasher =
+                    || BLAKE3Hasher::with_output_bits(output_bits).with_threads(threads);
+
+                Hmac::<BLAKE3>::with_hasher(key, new_hasher).finalize_stream(message)
+            }
+            // Refused when the options are parsed.
+            Algorithm::Sysv
+            | Algorithm::Bsd
+            | Algorithm::Crc
+            | Algorithm::Crc32b
+            | Algorithm::Crc32c
+            | Algorithm::Crc64 => unreachable!(),
+        }
+    }
 }
 
 impl State {
     fn process_arguments() -> Self {
-        let mut end_of_command_options = false;
-        let mut long_option;
-        let mut args: Vec<String> = env::args().collect();
-        args.remove(0);
-        let mut arguments: Vec<String> = Vec::new();
+        let mut arguments: Vec<OsString> = Vec::new();
         let mut base64 = false;
         let mut output_untagged = false;
-        let mut algorithm = Algorithm::MD5;
+        let mut zero = false;
+        let mut files0_from = None;
+        let mut check = false;
+        let mut check_options = CheckOptions::default();
+        // Last option only meaningful with '--check', to report if it is missing.
+        let mut check_only_option = None;
+        let mut algorithms: Vec<Algorithm> = Vec::new();
+        let mut length = None;
+        let mut key_file = None;
+        let mut derive_key = None;
+        let mut hmac_key_file = None;
+        let mut recursive = false;
+        let mut walk_options = WalkOptions::default();
+        let mut tree_digest = false;
+        let mut jobs = 0;
+        let mut buffer_size = DEFAULT_BUFFER_SIZE;
 
-        let mut args_iter = args.iter();
-        while let Some(argument) = args_iter.next() {
-            long_option = false;
-
-            assert!(argument.len() != 0, "Stdin argument was somehow empty??");
-            let arg_slice = if argument.len() == 1 {
-                &argument[0..1]
-            } else {
-                &argument[0..2]
-            };
-
-            match arg_slice {
-                "-a" if !end_of_command_options => {
-                    if args.len() == 2 {
-                        let next_arg = args_iter.next();
-                        algorithm = match next_arg {
-                            Some(arg) => match arg.as_str() {
-                                "md5" => Algorithm::MD5,
-                                "sha1" => Algorithm::SHA1,
-                                _ => {
-                                    print_help_invalid_argument(
-                                        arg,
-                                        "-a",
-                                        HELP_ALGORITHM_ARGUMENTS,
-                                    );
-                                    // Redudant as rust can't see that print_help_invalid_argument exits aswell.
-                                    process::exit(1);
-                                }
-                            },
-                            None => {
-                                print_help_option_requires_argument("-a");
-                                // Redudant as rust can't see that print_help_invalid_argument exits aswell.
-                                process::exit(1);
-                            }
-                        }
-                    } else {
-                        algorithm = match &argument[2..argument.len()] {
-                            "md5" => Algorithm::MD5,
-                            "sha1" => Algorithm::SHA1,
-                            _ => {
-                                print_help_invalid_argument(
-                                    argument,
-                                    "-a",
-                                    HELP_ALGORITHM_ARGUMENTS,
-                                );
-                                algorithm
-                            }
-                        }
-                    }
+        for arg in Parser::new(OPTIONS, env::args_os().skip(1)) {
+            let (option, argument) = match arg {
+                Ok(Arg::Option(option, argument)) => (option, argument.unwrap_or_default()),
+                Ok(Arg::Operand(operand)) => {
+                    arguments.push(operand);
+                    continue;
                 }
-                "-b" if !end_of_command_options => {
-                    base64 = true;
-                }
-                "-h" if !end_of_command_options => {
-                    print_help();
-                }
-                "-u" if !end_of_command_options => {
-                    output_untagged = true;
-                }
-                "-V" if !end_of_command_options => {
-                    print_version();
-                }
-                "--" if !end_of_command_options => {
-                    if argument == "--" {
-                        end_of_command_options = true
-                    } else {
-                        long_option = true;
-                    }
-                }
-                _ if !end_of_command_options && arg_slice[0..1] == *"-" && argument.len() > 1 => {
-                    print_help_invalid_option(argument)
+                Err(e) => {
+                    print_help_parse_error(e);
+                    // Redudant as rust can't see that print_help_parse_error exits aswell.
+                    process::exit(1);
                 }
-                _ => arguments.push(argument.to_string()),
             };
 
-            match argument.as_str() {
-                "--algorithm" if long_option => {
-                    let next_arg = args_iter.next();
-                    algorithm = match next_arg {
-                        Some(arg) => match arg.as_str() {
-                            "md5" => Algorithm::MD5,
-                            "sha1" => Algorithm::SHA1,
-                            _ => {
-                                print_help_invalid_argument(
-                                    arg,
-                                    "--algorithm",
-                                    HELP_ALGORITHM_ARGUMENTS,
-                                );
-                                // Redudant as rust can't see that print_help_invalid_argument exits aswell.
-                                process::exit(1);
-                            }
-                        },
-                        None => {
-                            print_help_invalid_argument(
-                                "",
+            match option {
+                "algorithm" => {
+                    for name in unicode_argument(option, &argument).split(',') {
+                        match Algorithm::from_argument(name) {
+                            Some(selected) if algorithms.contains(&selected) => {}
+                            Some(selected) => algorithms.push(selected),
+                            None => print_help_invalid_argument(
+                                name,
                                 "--algorithm",
                                 HELP_ALGORITHM_ARGUMENTS,
-                            );
-                            // Redudant as rust can't see that print_help_invalid_argument exits aswell.
-                            process::exit(1);
+                            ),
                         }
-                    };
-                }
-                "--untagged" if long_option => output_untagged = true,
-                "--version" if long_option => print_version(),
-                "--base64" if long_option => base64 = true,
-                "--help" if long_option => print_help(),
-                _ if long_option => {
-                    if argument.len() >= "--algorithm=".len() && argument[0..11] == *"--algorithm" {
-                        algorithm = match &argument[12..argument.len()] {
-                            "md5" => Algorithm::MD5,
-                            "sha1" => Algorithm::SHA1,
-                            _ => {
-                                print_help_invalid_argument(
-                                    &argument[12..argument.len()],
-                                    "--algorithm",
-                                    HELP_ALGORITHM_ARGUMENTS,
-                                );
-                                // Redudant as rust can't see that print_help_invalid_argument exits aswell.
-                                process::exit(1);
-                            }
-                        };
-                    } else {
-                        print_help_unrecognised_option(argument);
                     }
                 }
-                _ => (), // Ignore this match statement if it is a short option,
+                "base64" => base64 = true,
+                "buffer-size" => {
+                    buffer_size = parse_buffer_size(unicode_argument(option, &argument))
+                }
+                "check" => check = true,
+                "dereference" => walk_options.follow_symlinks = true,
+                "derive-key" => derive_key = Some(unicode_argument(option, &argument).to_string()),
+                "exclude" => walk_options
+                    .exclude
+                    .push(Pattern::new(unicode_argument(option, &argument))),
+                "files0-from" => files0_from = Some(argument),
+                "help" => print_help(),
+                "hmac-key-file" => hmac_key_file = Some(argument),
+                "ignore-missing" => check_options.ignore_missing = true,
+                "include" => walk_options
+                    .include
+                    .push(Pattern::new(unicode_argument(option, &argument))),
+                "jobs" => jobs = parse_jobs(unicode_argument(option, &argument)),
+                "key-file" => key_file = Some(argument),
+                "length" => length = Some(parse_length(unicode_argument(option, &argument))),
+                "one-file-system" => walk_options.one_file_system = true,
+                // As in coreutils, the last of '--quiet', '--status' and '--warn' wins.
+                "quiet" => {
+                    check_options.quiet = true;
+                    check_options.status = false;
+                    check_options.warn = false;
+                }
+                "recursive" => recursive = true,
+                "status" => {
+                    check_options.quiet = false;
+                    check_options.status = true;
+                    check_options.warn = false;
+                }
+                "strict" => check_options.strict = true,
+                "tree-digest" => tree_digest = true,
+                "untagged" => output_untagged = true,
+                "version" => print_version(),
+                "zero" => zero = true,
+                "warn" => {
+                    check_options.quiet = false;
+                    check_options.status = false;
+                    check_options.warn = true;
+                }
+                _ => unreachable!("option missing from OPTIONS: {option}"),
             }
+            if matches!(
+                option,
+                "ignore-missing" | "quiet" | "status" | "strict" | "warn"
+            ) {
+                check_only_option = Some(option);
+            }
+        }
+
+        // Like cksum, the POSIX CRC is used when no algorithm is selected. It has no tagged
+        // output, so checking is only refused when it was selected explicitly.
+        if check && algorithms.iter().any(Algorithm::is_legacy_checksum) {
+            eprintln!(
+                "hashsum: --check is not supported with --algorithm={{bsd,sysv,crc,crc32b}}\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        // Their output doesn't name the algorithm, so their lines couldn't be told apart.
+        if algorithms.len() > 1 && algorithms.iter().any(Algorithm::is_legacy_checksum) {
+            eprintln!(
+                "hashsum: --algorithm={{bsd,sysv,crc,crc32b}} can't be combined with other algorithms\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        // Checksum files name the algorithm of each tagged line, so only one default is needed.
+        if check && algorithms.len() > 1 {
+            eprintln!(
+                "hashsum: --check is not supported with several algorithms\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        if let Some(option) = check_only_option
+            && !check
+        {
+            eprintln!(
+                "hashsum: the --{option} option is meaningful only when checking checksums\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        if files0_from.is_some()
+            && let Some(operand) = arguments.first()
+        {
+            eprintln!(
+                "hashsum: extra operand '{}'\nfile operands cannot be combined with --files0-from\n{HELP_INFO_STRING}",
+                operand.display()
+            );
+            process::exit(1);
+        }
+        let arguments = match &files0_from {
+            Some(list) => read_files0_from(list),
+            None => arguments.into_iter().map(Ok).collect(),
+        };
+        let files_from_list = files0_from.is_some();
+        if check && zero {
+            eprintln!(
+                "hashsum: the --zero option is not supported when checking checksums\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
         }
+        if check && recursive {
+            eprintln!("hashsum: --recursive is not supported with --check\n{HELP_INFO_STRING}");
+            process::exit(1);
+        }
+        if tree_digest && recursive {
+            eprintln!(
+                "hashsum: --tree-digest and --recursive are mutually exclusive\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        if tree_digest
+            && !check
+            && ((arguments.is_empty() && !files_from_list)
+                || arguments.iter().any(|a| matches!(a, Ok(a) if a == "-")))
+        {
+            eprintln!("hashsum: --tree-digest can't hash standard input\n{HELP_INFO_STRING}");
+            process::exit(1);
+        }
+        let blake3_keyed = key_file.is_some() || derive_key.is_some();
+        let hmac_key = hmac_key(&algorithms, check, blake3_keyed, hmac_key_file);
+        let blake3_mode = blake3_mode(&algorithms, check, key_file, derive_key);
+        if algorithms.is_empty() {
+            algorithms.push(Algorithm::Crc);
+        }
+        let variable_length = algorithms
+            .iter()
+            .any(|algorithm| algorithm.max_output_bits().is_some());
 
         Self {
             arguments,
+            files_from_list,
             base64,
             output_untagged,
-            algorithm,
+            zero,
+            check,
+            check_options,
+            blake3_mode,
+            algorithms: algorithms
+                .iter()
+                .map(|&algorithm| {
+                    // Among several algorithms, '--length' only applies to the variable length
+                    // ones, and is refused only if there are none.
+                    let length = if variable_length && algorithm.max_output_bits().is_none() {
+                        None
+                    } else {
+                        length
+                    };
+                    (algorithm, validate_length(algorithm, length))
+                })
+                .collect(),
+            hmac_key,
+            recursive,
+            walk_options,
+            tree_digest,
+            jobs: if jobs == 0 { default_jobs() } else { jobs },
+            buffer_size,
         }
     }
-}
 
-fn main() {
-    let state = State::process_arguments();
-    let mut counter = 0;
-    let mut no_args = false;
+    /// Returns the byte ending each output line, NUL with '--zero'.
+    fn line_end(&self) -> u8 {
+        if self.zero { b'\0' } else { b'\n' }
+    }
+}
 
-    if state.arguments.len() == 0 {
-        no_args = true;
+/// Returns the argument given to `option`, exiting if it isn't valid Unicode as only file names
+/// need not be.
+fn unicode_argument<'a>(option: &str, argument: &'a OsStr) -> &'a str {
+    match argument.to_str() {
+        Some(argument) => argument,
+        None => {
+            eprintln!(
+                "hashsum: invalid argument '{}' for '--{option}'\n{HELP_INFO_STRING}",
+                argument.display()
+            );
+            process::exit(1);
+        }
     }
+}
 
-    loop {
-        let mut data: Vec<u8>;
-        let hashed_result: String;
+/// Parses the argument to '--length', exiting if it isn't a number.
+fn parse_length(argument: &str) -> usize {
+    match argument.parse() {
+        Ok(f) => f,
+        Err(_) => {
+            print_help_invalid_length(argument, "length is not a number");
+            // Redudant as rust can't see that print_help_invalid_length exits aswell.
+            process::exit(1);
+        }
+    }
+}
 
-        if no_args || state.arguments[counter] == "-" {
-            // Read data from stdin
-            let mut stdin = String::new();
+/// Reads the NUL-terminated file names listed in `list` by '--files0-from', exiting if the
+/// list can't be read. Entries that can't name a file are kept in their place as errors, so like
+/// coreutils they are reported while the other files are still hashed.
+fn read_files0_from(list: &OsStr) -> Vec<Operand> {
+    let contents = if list == "-" {
+        let mut contents = Vec::new();
+        io::stdin()
+            .lock()
+            .read_to_end(&mut contents)
+            .map(|_| contents)
+    } else {
+        fs::read(list)
+    };
+    let contents = match contents {
+        Ok(contents) => contents,
+        Err(e) => {
+            print_error(list.display(), e);
+            process::exit(1);
+        }
+    };
 
-            let mut bytes_read = io::stdin().read_line(&mut stdin).unwrap_or(0);
-            while bytes_read != 0 {
-                bytes_read = match io::stdin().read_line(&mut stdin) {
-                    Ok(f) => f,
-                    Err(error) => {
-                        eprintln!("Error reading stdin: {}", error);
-                        process::exit(1);
-                    }
-                }
-            }
+    // The last name doesn't need to be terminated.
+    let contents = contents.strip_suffix(b"\0").unwrap_or(&contents);
+    if contents.is_empty() {
+        return Vec::new();
+    }
 
-            data = stdin.clone().into_bytes();
-            hashed_result = match state.algorithm {
-                Algorithm::MD5 => MD5::hash_slice(&mut data),
-                Algorithm::SHA1 => SHA1::hash_slice(&mut data),
-            }
+    let mut names = Vec::new();
+    for (number, name) in (1..).zip(contents.split(|byte| *byte == 0)) {
+        let reason = if name.is_empty() {
+            "invalid zero-length file name"
+        } else if list == "-" && name == b"-" {
+            "when reading file names from standard input, no file name of '-' allowed"
+        } else if let Some(name) = filename::from_bytes(name) {
+            names.push(Ok(name));
+            continue;
         } else {
-            // Read data from file passed as argument
-            let file_handle = match File::open(&state.arguments[counter]) {
+            "file name is not valid UTF-8"
+        };
+
+        names.push(Err(InvalidEntry {
+            location: format!("{}:{number}", list.display()),
+            reason,
+        }));
+    }
+
+    names
+}
+
+/// Parses the argument to '--jobs', exiting if it isn't a number.
+fn parse_jobs(argument: &str) -> usize {
+    match argument.parse() {
+        Ok(f) => f,
+        Err(_) => {
+            eprintln!("hashsum: invalid number of jobs: \'{argument}\'\n{HELP_INFO_STRING}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Parses the argument to '--buffer-size', a number of bytes with an optional K, M or G suffix,
+/// exiting if it isn't a size of at least one byte.
+fn parse_buffer_size(argument: &str) -> usize {
+    let (number, unit) = match argument.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&argument[..index], KIB),
+        Some((index, 'M' | 'm')) => (&argument[..index], MIB),
+        Some((index, 'G' | 'g')) => (&argument[..index], GIB),
+        _ => (argument, 1),
+    };
+
+    match number
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit))
+    {
+        Some(size) if size > 0 => size,
+        _ => {
+            eprintln!("hashsum: invalid buffer size: \'{argument}\'\n{HELP_INFO_STRING}");
+            process::exit(1);
+        }
+    }
+}
+
+/// Returns the number of files to hash at once when '--jobs' isn't given, one per CPU.
+fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |threads| threads.get())
+}
+
+/// Checks a '--length' value against the selected algorithm, exiting if it is unsupported.
+/// Returns None when the algorithm's default length should be used.
+fn validate_length(algorithm: Algorithm, length: Option<usize>) -> Option<usize> {
+    let bits = match length {
+        None | Some(0) => return None,
+        Some(bits) => bits,
+    };
+
+    let Some(max_output_bits) = algorithm.max_output_bits() else {
+        eprintln!(
+            "hashsum: --length is not supported with \'{}\'\n{HELP_INFO_STRING}",
+            algorithm.tag()
+        );
+        process::exit(1);
+    };
+
+    if !bits.is_multiple_of(8) {
+        print_help_invalid_length(bits, "length is not a multiple of 8");
+    } else if !algorithm.supports_output_bits(bits) {
+        print_help_invalid_length(
+            bits,
+            format!(
+                "maximum digest length for \'{}\' is {max_output_bits} bits",
+                algorithm.tag()
+            ),
+        );
+    }
+
+    if Some(bits) == algorithm.default_output_bits() {
+        return None;
+    }
+
+    Some(bits)
+}
+
+/// Reads the key passed to '--hmac-key-file'. Exits if the key can't be read or HMACs aren't
+/// supported by every one of `algorithms`, which is empty when no algorithm was selected.
+fn hmac_key(
+    algorithms: &[Algorithm],
+    check: bool,
+    blake3_keyed: bool,
+    hmac_key_file: Option<OsString>,
+) -> Option<Vec<u8>> {
+    let hmac_key_file = hmac_key_file?;
+
+    if blake3_keyed {
+        eprintln!(
+            "hashsum: --hmac-key-file can't be combined with --key-file or --derive-key\n{HELP_INFO_STRING}"
+        );
+        process::exit(1);
+    }
+
+    // Checking takes the algorithm from tagged lines, so the default CRC is only refused when
+    // creating digests.
+    let algorithms = if algorithms.is_empty() && !check {
+        &[Algorithm::Crc]
+    } else {
+        algorithms
+    };
+    if let Some(algorithm) = algorithms
+        .iter()
+        .find(|algorithm| !algorithm.supports_hmac())
+    {
+        eprintln!(
+            "hashsum: --hmac-key-file is not supported with \'{}\'\n{HELP_INFO_STRING}",
+            algorithm.tag()
+        );
+        process::exit(1);
+    }
+
+    match fs::read(&hmac_key_file) {
+        Ok(key) => Some(key),
+        Err(e) => {
+            print_error(hmac_key_file.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Selects the BLAKE3 mode from the '--key-file' and '--derive-key' options, reading the key
+/// file. Exits if the options conflict, the key can't be read or BLAKE3 isn't selected, which
+/// `algorithms` being empty means when checking.
+fn blake3_mode(
+    algorithms: &[Algorithm],
+    check: bool,
+    key_file: Option<OsString>,
+    derive_key: Option<String>,
+) -> Blake3Mode {
+    // Checking takes the algorithm from tagged lines, so the options are allowed without one.
+    let selected = algorithms.contains(&Algorithm::BLAKE3) || (check && algorithms.is_empty());
+    if !selected && (key_file.is_some() || derive_key.is_some()) {
+        eprintln!(
+            "hashsum: --key-file and --derive-key are only supported with \'blake3\'\n{HELP_INFO_STRING}"
+        );
+        process::exit(1);
+    }
+
+    match (key_file, derive_key) {
+        (None, None) => Blake3Mode::Hash,
+        (Some(_), Some(_)) => {
+            eprintln!(
+                "hashsum: --key-file and --derive-key are mutually exclusive\n{HELP_INFO_STRING}"
+            );
+            process::exit(1);
+        }
+        (Some(key_file), None) => {
+            let key = match fs::read(&key_file) {
                 Ok(f) => f,
                 Err(e) => {
-                    eprintln!("Error opening file {}: {}", state.arguments[counter], e);
+                    print_error(key_file.display(), e);
                     process::exit(1);
                 }
             };
 
-            let message = BufReader::with_capacity(FILE_BUFFER, file_handle);
-
-            hashed_result = match MD5::hash_stream(message) {
-                Ok(f) => f,
-                Err(e) => {
-                    eprintln!("Error opening file {}: {}", state.arguments[counter], e);
+            match key.try_into() {
+                Ok(key) => Blake3Mode::KeyedHash(key),
+                Err(_) => {
+                    eprintln!(
+                        "hashsum: {}: key must be exactly 32 bytes",
+                        key_file.display()
+                    );
                     process::exit(1);
                 }
             }
         }
+        (None, Some(context)) => Blake3Mode::DeriveKey(context),
+    }
+}
+
+/// Reader counting the bytes passed through it, for the sizes printed by the legacy checksums.
+struct CountingReader<R> {
+    inner: R,
+    bytes: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        let bytes = self.inner.read(buffer)?;
+        self.bytes += bytes as u64;
+        Ok(bytes)
+    }
+}
+
+/// Hashes a regular file through a memory map. Returns None if the file can't be mapped or
+/// changed while it was hashed, in which case it should be streamed instead.
+fn hash_mapped(
+    file: &File,
+    metadata: &Metadata,
+    hash: &dyn Fn(&mut dyn Read) -> io::Result<Vec<Digest>>,
+) -> Option<io::Result<(Vec<Digest>, u64)>> {
+    // SAFETY: Only other processes write the files being hashed. A truncated file reads as zeros
+    // rather than raising SIGBUS, and like any other change to its size or modification time is
+    // caught below.
+    let mut reader = unsafe { MappedReader::new(file, metadata.len()) }.ok()?;
+    let digests = hash(&mut reader);
+
+    let unchanged = !reader.changed_size()
+        && file.metadata().is_ok_and(|current| {
+            current.len() == metadata.len() && current.modified().ok() == metadata.modified().ok()
+        });
+    if !unchanged {
+        return None;
+    }
+
+    Some(digests.map(|digests| (digests, metadata.len())))
+}
+
+/// Returns the size of the buffer for reading a file with `metadata` in reads of `buffer_size`
+/// bytes, which is smaller for a regular file that fits in fewer bytes.
+fn read_capacity(metadata: &Metadata, buffer_size: usize) -> usize {
+    // Pipes and devices have no useful size, so they always get the whole buffer.
+    if !metadata.is_file() {
+        return buffer_size;
+    }
+
+    usize::try_from(metadata.len())
+        .unwrap_or(usize::MAX)
+        .clamp(MIN_BUFFER_SIZE.min(buffer_size), buffer_size)
+}
+
+/// Hashes the file named by `argument` with each of `algorithms` into a digest of its length in
+/// bits if set, streaming stdin instead when the argument is '-'. Returns the digests in the
+/// order of `algorithms` and the number of bytes hashed. Several algorithms share a single read
+/// of the file, each hashing on its own thread. Computes an HMAC instead when `hmac_key` is set,
+/// and hashes the serialisation of the tree at `argument` when `tree_digest` is set. The
+/// algorithms share at most `threads` threads between them for hashing that can be split up.
+///
+/// Regular files larger than `buffer_size` are hashed through a memory map where supported,
+/// while other files are read `buffer_size` bytes at a time, or in one read if smaller.
+fn hash_argument(
+    algorithms: &[(Algorithm, Option<usize>)],
+    blake3_mode: &Blake3Mode,
+    hmac_key: Option<&[u8]>,
+    tree_digest: bool,
+    buffer_size: usize,
+    threads: usize,
+    argument: &OsStr,
+) -> io::Result<(Vec<Digest>, u64)> {
+    let threads = (threads / algorithms.len()).max(1);
+    let hash_one = |(algorithm, output_bits): (Algorithm, Option<usize>), reader: &mut dyn Read| {
+        match hmac_key {
+            Some(key) => algorithm.hmac_stream(reader, key, output_bits, threads),
+            None => algorithm.hash_stream(reader, output_bits, blake3_mode, threads),
+        }
+    };
+    let hash = |reader: &mut dyn Read| match algorithms {
+        [algorithm] => Ok(vec![hash_one(*algorithm, reader)?]),
+        _ => fan_out(reader, algorithms.len(), buffer_size, |index, reader| {
+            hash_one(algorithms[index], reader)
+        }),
+    };
+
+    if tree_digest {
+        let mut reader = CountingReader {
+            inner: TreeReader::new(argument)?,
+            bytes: 0,
+        };
+        let digests = hash(&mut reader)?;
+        Ok((digests, reader.bytes))
+    } else if argument == "-" {
+        let mut reader = CountingReader {
+            inner: BufReader::with_capacity(buffer_size, io::stdin().lock()),
+            bytes: 0,
+        };
+        let digests = hash(&mut reader)?;
+        Ok((digests, reader.bytes))
+    } else {
+        let file_handle = File::open(argument)?;
+        let metadata = file_handle.metadata()?;
+        if metadata.is_file()
+            && metadata.len() > buffer_size as u64
+            && let Some(result) = hash_mapped(&file_handle, &metadata, &hash)
+        {
+            return result;
+        }
 
-        if !no_args {
-            println!("{} {}", hashed_result, state.arguments[counter]);
+        let mut reader = CountingReader {
+            inner: BufReader::with_capacity(read_capacity(&metadata, buffer_size), file_handle),
+            bytes: 0,
+        };
+        let digests = hash(&mut reader)?;
+        Ok((digests, reader.bytes))
+    }
+}
+
+/// Prints a sum or cksum checksum in the layout of the matching coreutils program. As with
+/// coreutils the filename is left out when reading stdin because no files were given.
+fn print_legacy_checksum(
+    state: &State,
+    algorithm: Algorithm,
+    digest: &Digest,
+    size: u64,
+    filename: &OsStr,
+) {
+    let bytes = digest.as_bytes();
+    let checksum = match algorithm {
+        Algorithm::Sysv => format!(
+            "{} {}",
+            u16::from_be_bytes([bytes[0], bytes[1]]),
+            size.div_ceil(512)
+        ),
+        Algorithm::Bsd => format!(
+            "{:05} {:>5}",
+            u16::from_be_bytes([bytes[0], bytes[1]]),
+            size.div_ceil(1024)
+        ),
+        _ => format!(
+            "{} {size}",
+            u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        ),
+    };
+
+    let end = [state.line_end()];
+    if state.arguments.is_empty() && !state.files_from_list {
+        print_bytes(&[checksum.as_bytes(), &end].concat());
+    } else {
+        print_bytes(&[checksum.as_bytes(), b" ", filename.as_encoded_bytes(), &end].concat());
+    }
+}
+
+/// Prints a digest in the BSD-style tagged layout 'MD5 (filename) = digest', or the untagged
+/// layout 'digest  filename' when '--untagged' is set. Unless '--zero' is set, file names are
+/// escaped as coreutils does to keep each checksum on one line.
+fn print_checksum(
+    state: &State,
+    algorithm: Algorithm,
+    output_bits: Option<usize>,
+    digest: &Digest,
+    filename: &OsStr,
+) {
+    let encoded_digest = if state.base64 {
+        digest.to_base64()
+    } else {
+        digest.to_hex()
+    };
+
+    let name = filename.as_encoded_bytes();
+    let escaped_name = if state.zero {
+        None
+    } else {
+        filename::escape(name)
+    };
+    let escape_prefix: &[u8] = if escaped_name.is_some() { b"\\" } else { b"" };
+    let name = escaped_name.as_deref().unwrap_or(name);
+    let end = [state.line_end()];
+
+    if state.output_untagged {
+        print_bytes(&[escape_prefix, encoded_digest.as_bytes(), b"  ", name, &end].concat());
+    } else {
+        let hmac_prefix = if state.hmac_key.is_some() {
+            "HMAC-"
+        } else {
+            ""
+        };
+        let tag = format!(
+            "{hmac_prefix}{}",
+            algorithm.tag_with_length(output_bits, state.blake3_mode.kind())
+        );
+        print_bytes(
+            &[
+                escape_prefix,
+                tag.as_bytes(),
+                b" (",
+                name,
+                b") = ",
+                encoded_digest.as_bytes(),
+                &end,
+            ]
+            .concat(),
+        );
+    }
+}
+
+/// A file to hash, or the error finding the files of a directory argument or reading an entry of
+/// a file list, in the order their results are printed.
+type Job = Result<OsString, WalkError>;
+
+/// Hashes the file named by `argument` with the options in `state` on at most `threads`
+/// threads.
+fn hash_file(state: &State, argument: &OsStr, threads: usize) -> io::Result<(Vec<Digest>, u64)> {
+    hash_argument(
+        &state.algorithms,
+        &state.blake3_mode,
+        state.hmac_key.as_deref(),
+        state.tree_digest,
+        state.buffer_size,
+        threads,
+        argument,
+    )
+}
+
+/// Prints the checksums of a job hashed to `result`, one per algorithm, or the error if it
+/// failed. Returns false if the job failed.
+fn print_result(state: &State, job: &Job, result: Option<io::Result<(Vec<Digest>, u64)>>) -> bool {
+    let argument = match job {
+        Ok(argument) => argument,
+        Err(e) => {
+            print_error(e.path.display(), &e.error);
+            return false;
+        }
+    };
+
+    let (digests, size) = match result {
+        Some(Ok(f)) => f,
+        Some(Err(e)) => {
+            print_hash_error(state, argument, e);
+            return false;
+        }
+        None => unreachable!("file jobs are always hashed"),
+    };
+
+    for (&(algorithm, output_bits), digest) in state.algorithms.iter().zip(&digests) {
+        if algorithm.is_legacy_checksum() {
+            print_legacy_checksum(state, algorithm, digest, size, argument);
         } else {
-            println!("{} -", hashed_result);
+            print_checksum(state, algorithm, output_bits, digest, argument);
+        }
+    }
+    true
+}
+
+/// Hashes `jobs` on up to `threads` threads, printing the results in the order of `jobs` as
+/// soon as each one and all before it are done. Returns false if any job failed.
+fn hash_jobs(state: &State, jobs: &[Job], threads: usize) -> bool {
+    // The threads are shared out between the jobs hashed at once, so algorithms splitting a file
+    // across threads don't multiply them.
+    let workers = threads.clamp(1, jobs.len().max(1));
+    let threads_per_job = (threads / workers).max(1);
+    let hash_job = |job: &Job| {
+        job.as_ref()
+            .ok()
+            .map(|file| hash_file(state, file, threads_per_job))
+    };
+    // There is only one stdin, so every '-' is hashed on the printing thread in turn, letting
+    // each take what the previous left of it rather than whichever worker reaches it first.
+    let reads_stdin = |job: &Job| matches!(job, Ok(file) if file == "-");
+    let mut success = true;
+
+    if threads <= 1 || jobs.len() <= 1 {
+        for job in jobs {
+            success &= print_result(state, job, hash_job(job));
+        }
+        return success;
+    }
+
+    let next_job = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let next_job = &next_job;
+            scope.spawn(move || {
+                loop {
+                    let index = next_job.fetch_add(1, Ordering::Relaxed);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+                    if reads_stdin(job) {
+                        continue;
+                    }
+                    // The receiver only hangs up if printing panicked, so there is nothing left
+                    // to do.
+                    if sender.send((index, hash_job(job))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Results finished ahead of an earlier job wait here until it is printed.
+        let mut finished = BTreeMap::new();
+        let mut received = receiver.iter();
+        for (index, job) in jobs.iter().enumerate() {
+            let result = if reads_stdin(job) {
+                hash_job(job)
+            } else {
+                loop {
+                    if let Some(result) = finished.remove(&index) {
+                        break result;
+                    }
+                    let (index, result) = received.next().expect("workers hash every file job");
+                    finished.insert(index, result);
+                }
+            };
+            success &= print_result(state, job, result);
+        }
+    });
+
+    success
+}
+
+fn main() {
+    let state = State::process_arguments();
+
+    if state.check {
+        if check::check_files(&state) {
+            process::exit(0);
+        }
+        process::exit(1);
+    }
+
+    let mut jobs: Vec<Job> = Vec::new();
+    if state.arguments.is_empty() && !state.files_from_list {
+        jobs.push(Ok(OsString::from("-")));
+    }
+
+    for argument in &state.arguments {
+        match argument {
+            Ok(argument) if state.recursive && argument != "-" && Path::new(argument).is_dir() => {
+                jobs.extend(walk::walk(argument, &state.walk_options));
+            }
+            Ok(argument) => jobs.push(Ok(argument.clone())),
+            Err(entry) => jobs.push(Err(WalkError {
+                path: entry.location.clone().into(),
+                error: io::Error::new(ErrorKind::InvalidInput, entry.reason),
+            })),
         }
+    }
+
+    // Like coreutils, every file is attempted and failures only change the exit status.
+    if !hash_jobs(&state, &jobs, state.jobs) {
+        process::exit(1);
+    }
+}
 
-        if counter + 1 >= state.arguments.len() {
-            break;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reader counting the reads made of it.
+    struct ReadCounter<R> {
+        inner: R,
+        reads: usize,
+    }
+
+    impl<R: Read> Read for ReadCounter<R> {
+        fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.inner.read(buffer)
         }
-        counter += 1;
+    }
+
+    #[test]
+    fn read_capacity_follows_buffer_size() {
+        let path = std::env::temp_dir().join(format!("hashsum-capacity-{}", std::process::id()));
+        let contents: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+        fs::write(&path, &contents).unwrap();
+        let file = File::open(&path).unwrap();
+        let metadata = file.metadata().unwrap();
+
+        assert_eq!(read_capacity(&metadata, 1), 1);
+        assert_eq!(read_capacity(&metadata, 1024), 1024);
+        assert_eq!(read_capacity(&metadata, DEFAULT_BUFFER_SIZE), 10_000);
+        assert_eq!(
+            read_capacity(
+                &fs::metadata("tests/data/abc.txt").unwrap(),
+                DEFAULT_BUFFER_SIZE
+            ),
+            MIN_BUFFER_SIZE
+        );
+
+        // A file larger than the buffer takes a read for each buffer's worth of it.
+        let mut reader = BufReader::with_capacity(
+            read_capacity(&metadata, 1024),
+            ReadCounter {
+                inner: file,
+                reads: 0,
+            },
+        );
+        let digest = MD5::hash_stream(&mut reader).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(digest, MD5::hash_slice(&contents));
+        assert!(reader.get_ref().reads >= 10);
     }
 }
@@ -0,0 +1,479 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! Reading regular files through memory maps. A file truncated while mapped would normally kill
+//! the process with SIGBUS, so a handler replaces the mapping with zeros instead and marks it as
+//! truncated, letting the caller discard what it read and stream the file.
+
+use std::{
+    fs::File,
+    io::{self, Read},
+};
+
+// Size of each window of a file mapped by MappedReader, a multiple of every page size, so the
+// memory held at once stays bounded however large the file is.
+const WINDOW_SIZE: u64 = 64 * 1024 * 1024;
+
+// The layouts of siginfo_t and struct sigaction below are shared by glibc and musl on these
+// architectures, as are the values of the constants.
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod ffi {
+    use std::ffi::{c_int, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const MAP_PRIVATE: c_int = 0x02;
+    pub const MAP_FIXED: c_int = 0x10;
+    pub const MAP_ANONYMOUS: c_int = 0x20;
+    pub const MAP_FAILED: *mut c_void = usize::MAX as *mut c_void;
+    pub const SIGBUS: c_int = 7;
+    pub const SA_SIGINFO: c_int = 4;
+
+    /// The leading fields of siginfo_t, which are all the handler reads.
+    #[repr(C)]
+    pub struct SigInfo {
+        pub signal: c_int,
+        pub errno: c_int,
+        pub code: c_int,
+        pub padding: c_int,
+        pub address: *mut c_void,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub struct SigAction {
+        pub handler: usize,
+        pub mask: [u64; 16],
+        pub flags: c_int,
+        pub restorer: usize,
+    }
+
+    unsafe extern "C" {
+        pub fn mmap(
+            address: *mut c_void,
+            length: usize,
+            protection: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        pub fn munmap(address: *mut c_void, length: usize) -> c_int;
+        pub fn sigaction(
+            signal: c_int,
+            action: *const SigAction,
+            previous: *mut SigAction,
+        ) -> c_int;
+    }
+}
+
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod guard {
+    use super::ffi;
+    use std::{
+        ffi::{c_int, c_void},
+        io, ptr,
+        sync::{
+            OnceLock,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
+    };
+
+    // Mappings that can be guarded at once. Further files are streamed instead of mapped.
+    const SLOTS: usize = 64;
+
+    /// The address range of a guarded mapping, empty while the slot is unused.
+    struct Slot {
+        claimed: AtomicBool,
+        start: AtomicUsize,
+        end: AtomicUsize,
+        truncated: AtomicBool,
+    }
+
+    static SLOTS_IN_USE: [Slot; SLOTS] = [const {
+        Slot {
+            claimed: AtomicBool::new(false),
+            start: AtomicUsize::new(0),
+            end: AtomicUsize::new(0),
+            truncated: AtomicBool::new(false),
+        }
+    }; SLOTS];
+
+    /// The SIGBUS action in place before the handler, restored for faults outside the mappings.
+    static PREVIOUS_ACTION: OnceLock<Option<ffi::SigAction>> = OnceLock::new();
+
+    /// Replaces a guarded mapping the fault occurred in with zeros so the read can continue, or
+    /// restores the previous action so the fault is handled as if this handler didn't exist.
+    extern "C" fn on_sigbus(_signal: c_int, info: *mut ffi::SigInfo, _context: *mut c_void) {
+        // SAFETY: The kernel passes a valid siginfo_t to handlers installed with SA_SIGINFO.
+        let address = unsafe { (*info).address } as usize;
+
+        for slot in &SLOTS_IN_USE {
+            let start = slot.start.load(Ordering::Acquire);
+            let end = slot.end.load(Ordering::Acquire);
+            if (start..end).contains(&address) {
+                // SAFETY: The range is a mapping owned by the slot, which its reader only
+                // accesses through raw pointers, so replacing its pages can't invalidate any
+                // reference.
+                let zeros = unsafe {
+                    ffi::mmap(
+                        start as *mut c_void,
+                        end - start,
+                        ffi::PROT_READ,
+                        ffi::MAP_PRIVATE | ffi::MAP_FIXED | ffi::MAP_ANONYMOUS,
+                        -1,
+                        0,
+                    )
+                };
+                if zeros != ffi::MAP_FAILED {
+                    slot.truncated.store(true, Ordering::Release);
+                    return;
+                }
+            }
+        }
+
+        let previous = match PREVIOUS_ACTION.get() {
+            Some(Some(previous)) => *previous,
+            // SIG_DFL, ending the process once the faulting instruction runs again.
+            _ => ffi::SigAction {
+                handler: 0,
+                mask: [0; 16],
+                flags: 0,
+                restorer: 0,
+            },
+        };
+        // SAFETY: Restoring a previously installed or the default action is always valid.
+        unsafe {
+            ffi::sigaction(ffi::SIGBUS, &previous, ptr::null_mut());
+        }
+    }
+
+    /// Installs the SIGBUS handler once, returning false if it couldn't be installed.
+    fn install() -> bool {
+        PREVIOUS_ACTION
+            .get_or_init(|| {
+                let action = ffi::SigAction {
+                    handler: on_sigbus as *const () as usize,
+                    mask: [0; 16],
+                    flags: ffi::SA_SIGINFO,
+                    restorer: 0,
+                };
+                let mut previous = action;
+                // SAFETY: Both pointers are valid for the call and the handler only uses
+                // async-signal-safe system calls.
+                let result = unsafe { ffi::sigaction(ffi::SIGBUS, &action, &mut previous) };
+                (result == 0).then_some(previous)
+            })
+            .is_some()
+    }
+
+    /// A slot guarding one mapping, released when dropped.
+    pub struct Guard(&'static Slot);
+
+    impl Guard {
+        /// Claims a slot for a mapping about to be made, installing the handler if needed.
+        pub fn claim() -> io::Result<Self> {
+            if !install() {
+                return Err(io::Error::other("can't install the SIGBUS handler"));
+            }
+
+            SLOTS_IN_USE
+                .iter()
+                .find(|slot| {
+                    slot.claimed
+                        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Relaxed)
+                        .is_ok()
+                })
+                .map(Guard)
+                .ok_or_else(|| io::Error::other("too many files mapped at once"))
+        }
+
+        /// Starts guarding `length` bytes mapped at `address`.
+        pub fn watch(&self, address: *const u8, length: usize) {
+            self.0.truncated.store(false, Ordering::Relaxed);
+            self.0.start.store(address as usize, Ordering::Release);
+            self.0
+                .end
+                .store(address as usize + length, Ordering::Release);
+        }
+
+        /// Stops guarding the mapping, which must happen before it is unmapped.
+        pub fn unwatch(&self) {
+            self.0.end.store(0, Ordering::Release);
+            self.0.start.store(0, Ordering::Release);
+        }
+
+        /// Returns true if the file was truncated under the mapping, leaving zeros in its place.
+        pub fn truncated(&self) -> bool {
+            self.0.truncated.load(Ordering::Acquire)
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.unwatch();
+            self.0.claimed.store(false, Ordering::Release);
+        }
+    }
+}
+
+/// A read only memory map of part of a file, unmapped when dropped.
+pub struct Mmap {
+    pointer: *const u8,
+    length: usize,
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    guard: guard::Guard,
+}
+
+impl Mmap {
+    /// Maps `length` bytes of `file` from `offset`, which must be a multiple of the page size.
+    /// The length must be non-zero. If the file is truncated while mapped the missing pages read
+    /// as zeros and [`truncated`](Self::truncated) returns true.
+    ///
+    /// # Safety
+    ///
+    /// Other changes to the file show through the mapping, so they must only be made by another
+    /// process, where they can't break the guarantees of references held by this one.
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    pub unsafe fn map(file: &File, offset: u64, length: usize) -> io::Result<Self> {
+        use std::{os::fd::AsRawFd, ptr};
+
+        if length == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "can't map an empty file",
+            ));
+        }
+        let guard = guard::Guard::claim()?;
+
+        // SAFETY: Mapping a fresh range chosen by the kernel can't alias existing memory, and the
+        // result is checked before use.
+        let pointer = unsafe {
+            ffi::mmap(
+                ptr::null_mut(),
+                length,
+                ffi::PROT_READ,
+                ffi::MAP_PRIVATE,
+                file.as_raw_fd(),
+                offset as i64,
+            )
+        };
+        if pointer == ffi::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        guard.watch(pointer.cast(), length);
+
+        Ok(Self {
+            pointer: pointer.cast(),
+            length,
+            guard,
+        })
+    }
+
+    /// Memory maps can't be guarded against truncation on this platform, so files are always
+    /// streamed instead.
+    #[cfg(not(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    pub unsafe fn map(_file: &File, _offset: u64, _length: usize) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "memory maps are not supported",
+        ))
+    }
+
+    /// Returns the number of bytes mapped.
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Returns true if the file was truncated while mapped, in which case some of the bytes
+    /// read from the mapping were zeros rather than its contents.
+    pub fn truncated(&self) -> bool {
+        #[cfg(all(
+            target_os = "linux",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        return self.guard.truncated();
+        #[cfg(not(all(
+            target_os = "linux",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        )))]
+        false
+    }
+
+    /// Copies the mapped bytes from `offset` into `buffer`, which must fit within the mapping.
+    fn copy_to(&self, offset: usize, buffer: &mut [u8]) {
+        assert!(offset + buffer.len() <= self.length);
+        // SAFETY: The range was checked to lie within the mapping, which stays readable until
+        // dropped. No reference into it is ever made, as the pages may be replaced by the SIGBUS
+        // handler or change with the file.
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.pointer.add(offset),
+                buffer.as_mut_ptr(),
+                buffer.len(),
+            );
+        }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        #[cfg(all(
+            target_os = "linux",
+            any(target_arch = "x86_64", target_arch = "aarch64")
+        ))]
+        {
+            self.guard.unwatch();
+            // SAFETY: The range was mapped by `map` and nothing borrows it.
+            unsafe {
+                ffi::munmap(self.pointer.cast_mut().cast(), self.length);
+            }
+        }
+    }
+}
+
+/// Reader over the first `length` bytes of a file through successive memory mapped windows.
+/// Reading ends early once the file is found to have changed size, which
+/// [`changed_size`](Self::changed_size) reports.
+pub struct MappedReader<'a> {
+    file: &'a File,
+    length: u64,
+    /// Offset in the file of the current window.
+    window_offset: u64,
+    window: Mmap,
+    /// Position within the current window.
+    position: usize,
+    changed_size: bool,
+}
+
+impl<'a> MappedReader<'a> {
+    /// Maps the first window of `file`, failing if the file can't be memory mapped.
+    ///
+    /// # Safety
+    ///
+    /// As with [`Mmap::map`], the file must only be modified by other processes while read.
+    pub unsafe fn new(file: &'a File, length: u64) -> io::Result<Self> {
+        // SAFETY: Passed on to the caller.
+        let window = unsafe { Mmap::map(file, 0, length.min(WINDOW_SIZE) as usize)? };
+
+        Ok(Self {
+            file,
+            length,
+            window_offset: 0,
+            window,
+            position: 0,
+            changed_size: false,
+        })
+    }
+
+    /// Returns true if the file was truncated or changed size while read, so what was read
+    /// doesn't match its contents.
+    pub fn changed_size(&self) -> bool {
+        self.changed_size || self.window.truncated()
+    }
+}
+
+impl Read for MappedReader<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        if self.changed_size() {
+            self.changed_size = true;
+            return Ok(0);
+        }
+
+        if self.position == self.window.len() {
+            let next_offset = self.window_offset + self.window.len() as u64;
+            if next_offset >= self.length {
+                return Ok(0);
+            }
+            if self.file.metadata()?.len() != self.length {
+                self.changed_size = true;
+                return Ok(0);
+            }
+
+            let length = (self.length - next_offset).min(WINDOW_SIZE) as usize;
+            // SAFETY: The caller of `new` guaranteed the file is only modified by other
+            // processes.
+            self.window = unsafe { Mmap::map(self.file, next_offset, length)? };
+            self.window_offset = next_offset;
+            self.position = 0;
+        }
+
+        let bytes = buffer.len().min(self.window.len() - self.position);
+        self.window.copy_to(self.position, &mut buffer[..bytes]);
+        self.position += bytes;
+        Ok(bytes)
+    }
+}
+
+#[cfg(all(
+    test,
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod tests {
+    use super::*;
+    use std::fs::{self, OpenOptions};
+
+    fn temporary_file(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("hashsum-{name}-{}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn read_windows() {
+        let contents: Vec<u8> = (0..WINDOW_SIZE + 5000).map(|i| (i % 251) as u8).collect();
+        let path = temporary_file("mmap-windows", &contents);
+        let file = File::open(&path).unwrap();
+
+        let mut read = Vec::new();
+        // SAFETY: The file belongs to this test and isn't modified while mapped.
+        let mut reader = unsafe { MappedReader::new(&file, contents.len() as u64) }.unwrap();
+        reader.read_to_end(&mut read).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(!reader.changed_size());
+        assert!(read == contents);
+        // SAFETY: As above.
+        assert!(unsafe { Mmap::map(&file, 0, 0) }.is_err());
+    }
+
+    #[test]
+    fn truncated_while_read() {
+        let contents = vec![1; 1024 * 1024];
+        let path = temporary_file("mmap-truncated", &contents);
+        let file = File::open(&path).unwrap();
+
+        // SAFETY: The file is only truncated, which the reader is guarded against.
+        let mut reader = unsafe { MappedReader::new(&file, contents.len() as u64) }.unwrap();
+        let mut start = [0; 4096];
+        reader.read_exact(&mut start).unwrap();
+        OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(0)
+            .unwrap();
+
+        // Reading the pages that are gone raises SIGBUS, which must end the read rather than the
+        // process.
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(start, [1; 4096]);
+        assert!(reader.changed_size());
+    }
+}
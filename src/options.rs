@@ -0,0 +1,431 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::{self, Display},
+};
+
+/// A command line option recognised by `Parser`.
+pub struct OptionSpec {
+    /// Long name without the leading '--', which also identifies the option once parsed.
+    pub long: &'static str,
+    /// Name used after a single '-', if the option has one.
+    pub short: Option<char>,
+    /// True if the option requires an argument.
+    pub takes_argument: bool,
+}
+
+/// An item of the command line.
+#[derive(Debug, PartialEq)]
+pub enum Arg {
+    /// An option identified by its long name, with its argument if it takes one.
+    Option(&'static str, Option<OsString>),
+    /// Anything that isn't an option, such as a file name.
+    Operand(OsString),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// A long option matching no option, holding the argument as given.
+    UnrecognisedOption(String),
+    /// A short option matching no option.
+    InvalidOption(char),
+    /// A long option abbreviation matching several options, holding the argument as given and
+    /// the long names it matches.
+    AmbiguousOption(String, Vec<&'static str>),
+    /// A long option given without its required argument.
+    MissingLongArgument(&'static str),
+    /// A short option given without its required argument.
+    MissingShortArgument(char),
+    /// A long option given an argument with '=' that it doesn't take.
+    UnexpectedArgument(&'static str),
+}
+
+/// Parses command line arguments like GNU getopt_long, returning options and operands in the
+/// order given.
+///
+/// Short options may be grouped as in '-ub', and a short option's argument may follow it
+/// directly as in '-amd5' or be the next argument. Long options take their argument as
+/// '--length=8' or '--length 8', and may be abbreviated to any unambiguous prefix. A lone '-' is
+/// an operand, and everything after '--' is an operand. Operands and option arguments needn't
+/// be valid Unicode, while option names must be.
+pub struct Parser<'a, I: Iterator<Item = OsString>> {
+    options: &'a [OptionSpec],
+    args: I,
+    // Remaining characters of a group of short options, such as 'b' after the 'u' of '-ub'.
+    short_group: Option<OsString>,
+    end_of_options: bool,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnrecognisedOption(arg) => write!(f, "unrecognised option '{arg}'"),
+            ParseError::InvalidOption(option) => write!(f, "invalid option -- '{option}'"),
+            ParseError::AmbiguousOption(arg, candidates) => {
+                write!(f, "option '{arg}' is ambiguous; possibilities:")?;
+                for candidate in candidates {
+                    write!(f, " '--{candidate}'")?;
+                }
+                Ok(())
+            }
+            ParseError::MissingLongArgument(option) => {
+                write!(f, "option '--{option}' requires an argument")
+            }
+            ParseError::MissingShortArgument(option) => {
+                write!(f, "option requires an argument -- '{option}'")
+            }
+            ParseError::UnexpectedArgument(option) => {
+                write!(f, "option '--{option}' doesn't allow an argument")
+            }
+        }
+    }
+}
+
+/// Returns the part of `arg` after its first `start` bytes, which must end with an ASCII
+/// character.
+fn os_str_after(arg: &OsStr, start: usize) -> OsString {
+    let bytes = arg.as_encoded_bytes();
+    debug_assert!(bytes[start - 1].is_ascii());
+    // SAFETY: Encoded bytes may be split immediately after an ASCII character.
+    unsafe { OsStr::from_encoded_bytes_unchecked(&bytes[start..]) }.to_os_string()
+}
+
+impl<'a, I: Iterator<Item = OsString>> Parser<'a, I> {
+    pub fn new(options: &'a [OptionSpec], args: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            options,
+            args: args.into_iter(),
+            short_group: None,
+            end_of_options: false,
+        }
+    }
+
+    /// Finds the option named `name` exactly, or else the only option whose name starts with it.
+    fn find_long(&self, name: &str, arg: &str) -> Result<&'a OptionSpec, ParseError> {
+        if let Some(option) = self.options.iter().find(|option| option.long == name) {
+            return Ok(option);
+        }
+
+        let candidates: Vec<&'a OptionSpec> = self
+            .options
+            .iter()
+            .filter(|option| !name.is_empty() && option.long.starts_with(name))
+            .collect();
+        match candidates.as_slice() {
+            [] => Err(ParseError::UnrecognisedOption(arg.to_string())),
+            [option] => Ok(option),
+            _ => Err(ParseError::AmbiguousOption(
+                arg.to_string(),
+                candidates.iter().map(|option| option.long).collect(),
+            )),
+        }
+    }
+
+    fn parse_long(&mut self, arg: &OsStr) -> Result<Arg, ParseError> {
+        let bytes = arg.as_encoded_bytes();
+        let (name, inline_argument) = match bytes.iter().skip(2).position(|byte| *byte == b'=') {
+            Some(index) => (&bytes[2..index + 2], Some(os_str_after(arg, index + 3))),
+            None => (&bytes[2..], None),
+        };
+        let display_arg = arg.to_string_lossy();
+        let name = str::from_utf8(name)
+            .map_err(|_| ParseError::UnrecognisedOption(display_arg.to_string()))?;
+        let option = self.find_long(name, &display_arg)?;
+
+        let argument = match (option.takes_argument, inline_argument) {
+            (true, Some(argument)) => Some(argument),
+            (true, None) => Some(
+                self.args
+                    .next()
+                    .ok_or(ParseError::MissingLongArgument(option.long))?,
+            ),
+            (false, Some(_)) => return Err(ParseError::UnexpectedArgument(option.long)),
+            (false, None) => None,
+        };
+
+        Ok(Arg::Option(option.long, argument))
+    }
+
+    /// Parses the first option of a group of short options, keeping the rest for the next call.
+    fn parse_short(&mut self, group: OsString) -> Result<Arg, ParseError> {
+        let bytes = group.as_encoded_bytes();
+        // Short options are all ASCII, so a group starting with anything else is invalid.
+        if !bytes[0].is_ascii() {
+            let short = bytes
+                .utf8_chunks()
+                .next()
+                .and_then(|chunk| chunk.valid().chars().next())
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            return Err(ParseError::InvalidOption(short));
+        }
+        let short = char::from(bytes[0]);
+        let rest = os_str_after(&group, 1);
+
+        let option = self
+            .options
+            .iter()
+            .find(|option| option.short == Some(short))
+            .ok_or(ParseError::InvalidOption(short))?;
+
+        if !option.takes_argument {
+            if !rest.is_empty() {
+                self.short_group = Some(rest);
+            }
+            return Ok(Arg::Option(option.long, None));
+        }
+
+        let argument = if rest.is_empty() {
+            self.args
+                .next()
+                .ok_or(ParseError::MissingShortArgument(short))?
+        } else {
+            rest
+        };
+
+        Ok(Arg::Option(option.long, Some(argument)))
+    }
+}
+
+impl<I: Iterator<Item = OsString>> Iterator for Parser<'_, I> {
+    type Item = Result<Arg, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(group) = self.short_group.take() {
+            return Some(self.parse_short(group));
+        }
+
+        let arg = self.args.next()?;
+        if self.end_of_options {
+            return Some(Ok(Arg::Operand(arg)));
+        }
+
+        let bytes = arg.as_encoded_bytes();
+        if bytes == b"--" {
+            self.end_of_options = true;
+            self.next()
+        } else if bytes.starts_with(b"--") {
+            Some(self.parse_long(&arg))
+        } else if bytes.len() > 1 && bytes[0] == b'-' {
+            Some(self.parse_short(os_str_after(&arg, 1)))
+        } else {
+            Some(Ok(Arg::Operand(arg)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &[OptionSpec] = &[
+        OptionSpec {
+            long: "algorithm",
+            short: Some('a'),
+            takes_argument: true,
+        },
+        OptionSpec {
+            long: "base64",
+            short: Some('b'),
+            takes_argument: false,
+        },
+        OptionSpec {
+            long: "untagged",
+            short: Some('u'),
+            takes_argument: false,
+        },
+        OptionSpec {
+            long: "help",
+            short: Some('h'),
+            takes_argument: false,
+        },
+        OptionSpec {
+            long: "hmac-key-file",
+            short: None,
+            takes_argument: true,
+        },
+        OptionSpec {
+            long: "key-file",
+            short: None,
+            takes_argument: true,
+        },
+    ];
+
+    fn parse(args: &[&str]) -> Result<Vec<Arg>, ParseError> {
+        Parser::new(OPTIONS, args.iter().map(OsString::from)).collect()
+    }
+
+    fn option(name: &'static str, argument: Option<&str>) -> Arg {
+        Arg::Option(name, argument.map(OsString::from))
+    }
+
+    fn operand(operand: &str) -> Arg {
+        Arg::Operand(OsString::from(operand))
+    }
+
+    #[test]
+    fn short_options() {
+        let algorithm = Ok(vec![option("algorithm", Some("md5")), operand("file")]);
+
+        assert_eq!(parse(&["-a", "md5", "file"]), algorithm);
+        assert_eq!(parse(&["-amd5", "file"]), algorithm);
+        assert_eq!(
+            parse(&["-ub", "-a", "sha1", "a", "b"]),
+            Ok(vec![
+                option("untagged", None),
+                option("base64", None),
+                option("algorithm", Some("sha1")),
+                operand("a"),
+                operand("b"),
+            ])
+        );
+        // The rest of a group is the argument of an option taking one.
+        assert_eq!(
+            parse(&["-uamd5"]),
+            Ok(vec![
+                option("untagged", None),
+                option("algorithm", Some("md5"))
+            ])
+        );
+        // Arguments starting with '-' are still taken as the argument.
+        assert_eq!(
+            parse(&["-a", "-b"]),
+            Ok(vec![option("algorithm", Some("-b"))])
+        );
+    }
+
+    #[test]
+    fn long_options() {
+        let algorithm = Ok(vec![option("algorithm", Some("md5")), operand("file")]);
+
+        assert_eq!(parse(&["--algorithm", "md5", "file"]), algorithm);
+        assert_eq!(parse(&["--algorithm=md5", "file"]), algorithm);
+        assert_eq!(
+            parse(&["file", "--algorithm=md5"]),
+            Ok(vec![operand("file"), option("algorithm", Some("md5")),])
+        );
+        assert_eq!(
+            parse(&["--algorithm="]),
+            Ok(vec![option("algorithm", Some(""))])
+        );
+        assert_eq!(
+            parse(&["--key-file=a=b"]),
+            Ok(vec![option("key-file", Some("a=b"))])
+        );
+    }
+
+    #[test]
+    fn abbreviated_long_options() {
+        assert_eq!(
+            parse(&["--alg=md5", "--b", "--hm", "key"]),
+            Ok(vec![
+                option("algorithm", Some("md5")),
+                option("base64", None),
+                option("hmac-key-file", Some("key")),
+            ])
+        );
+        assert_eq!(
+            parse(&["--h"]),
+            Err(ParseError::AmbiguousOption(
+                "--h".to_string(),
+                vec!["help", "hmac-key-file"]
+            ))
+        );
+    }
+
+    #[test]
+    fn operands() {
+        assert_eq!(
+            parse(&["-", "a", "--", "-b", "--base64", "--"]),
+            Ok(vec![
+                operand("-"),
+                operand("a"),
+                operand("-b"),
+                operand("--base64"),
+                operand("--"),
+            ])
+        );
+        assert_eq!(parse(&[""]), Ok(vec![operand("")]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_unicode_arguments() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let name = || OsString::from_vec(b"caf\xe9".to_vec());
+        let prefixed = |prefix: &[u8]| OsString::from_vec([prefix, b"caf\xe9"].concat());
+        let args = [
+            prefixed(b"--key-file="),
+            prefixed(b"-a"),
+            OsString::from("--hmac-key-file"),
+            name(),
+            name(),
+        ];
+
+        assert_eq!(
+            Parser::new(OPTIONS, args).collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                Arg::Option("key-file", Some(name())),
+                Arg::Option("algorithm", Some(name())),
+                Arg::Option("hmac-key-file", Some(name())),
+                Arg::Operand(name()),
+            ])
+        );
+        assert_eq!(
+            Parser::new(OPTIONS, [prefixed(b"--")]).next(),
+            Some(Err(ParseError::UnrecognisedOption(
+                "--caf\u{fffd}".to_string()
+            )))
+        );
+        assert_eq!(
+            Parser::new(OPTIONS, [OsString::from_vec(b"-\xe9".to_vec())]).next(),
+            Some(Err(ParseError::InvalidOption('\u{fffd}')))
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse(&["--bogus"]),
+            Err(ParseError::UnrecognisedOption("--bogus".to_string()))
+        );
+        assert_eq!(
+            parse(&["--=md5"]),
+            Err(ParseError::UnrecognisedOption("--=md5".to_string()))
+        );
+        assert_eq!(parse(&["-ux"]), Err(ParseError::InvalidOption('x')));
+        assert_eq!(
+            parse(&["--algorithm"]),
+            Err(ParseError::MissingLongArgument("algorithm"))
+        );
+        assert_eq!(parse(&["-ua"]), Err(ParseError::MissingShortArgument('a')));
+        assert_eq!(
+            parse(&["--base64=yes"]),
+            Err(ParseError::UnexpectedArgument("base64"))
+        );
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            ParseError::AmbiguousOption("--h".to_string(), vec!["help", "hmac-key-file"])
+                .to_string(),
+            "option '--h' is ambiguous; possibilities: '--help' '--hmac-key-file'"
+        );
+        assert_eq!(
+            ParseError::MissingShortArgument('a').to_string(),
+            "option requires an argument -- 'a'"
+        );
+    }
+
+    #[test]
+    fn stops_at_first_error() {
+        // Options before an error are still returned, so '--help' can act before it.
+        let mut parser = Parser::new(OPTIONS, ["--help", "--bogus"].map(OsString::from));
+
+        assert_eq!(parser.next(), Some(Ok(option("help", None))));
+        assert!(matches!(parser.next(), Some(Err(_))));
+    }
+}
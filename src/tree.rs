@@ -0,0 +1,350 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+//! Canonical serialisation of a directory tree, so a whole tree can be hashed into one digest.
+//!
+//! [`TreeReader`] streams the serialisation of a tree, which any [`Hash`] implementation can
+//! digest with [`Hash::hash_stream`]. The digest changes with the names in the tree, the
+//! contents of its files, their executable bit and the targets of its symbolic links, but not
+//! with timestamps, ownership, other permission bits or the order entries are read from disk.
+//!
+//! # Format
+//!
+//! Version 1 of the format is the magic string `hashsum-tree-v1` followed by a zero byte, then
+//! the root node. Integers are 64 bit little-endian and every name or target is preceded by
+//! its length in bytes, so distinct trees never serialise to the same bytes.
+//!
+//! | Node              | Serialisation                                                  |
+//! |-------------------|----------------------------------------------------------------|
+//! | Regular file      | `f`, the file size, the contents                               |
+//! | Executable file   | `x`, the file size, the contents                               |
+//! | Symbolic link     | `l`, the target length, the target                             |
+//! | Directory         | `d`, the number of entries, then for each entry the name length, the name and the entry's node |
+//!
+//! Directory entries are ordered by the bytes of their names. Symbolic links are never
+//! followed, including the root. A file is executable if its owner may execute it, and on
+//! platforms without Unix permissions no file is executable. Other kinds of file, such as
+//! sockets and devices, can't be serialised. Any change to the format will change the magic
+//! string.
+
+use crate::hash_algorithm::{Hash, digest::Digest};
+use std::{
+    ffi::OsStr,
+    fs::{self, File, Metadata},
+    io::{self, ErrorKind, Read},
+    path::{Path, PathBuf},
+};
+
+/// Magic string starting version 1 of the serialisation.
+pub const MAGIC: &[u8] = b"hashsum-tree-v1\0";
+
+/// Reader streaming the canonical serialisation of a directory tree.
+///
+/// Files are read as the stream reaches them, so memory use depends on the size of the largest
+/// directory rather than the whole tree. Errors name the path they occurred at.
+pub struct TreeReader {
+    /// Serialised bytes waiting to be read, starting at `position`.
+    buffer: Vec<u8>,
+    position: usize,
+    /// File whose contents are being read, with the number of bytes its header promised.
+    file: Option<(File, PathBuf, u64)>,
+    /// Remaining entries of each directory being serialised, last entry first.
+    directories: Vec<Vec<(Vec<u8>, PathBuf)>>,
+}
+
+/// Hashes the tree at `path` with `H`.
+///
+/// ```
+/// # #[cfg(feature = "sha2")]
+/// # {
+/// use hashsum::{hash_algorithm::sha256::SHA256, tree};
+///
+/// let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data");
+/// let digest = tree::hash_tree::<SHA256>(path).unwrap();
+/// assert_eq!(digest.len(), 32);
+/// # }
+/// ```
+pub fn hash_tree<H: Hash>(path: impl AsRef<Path>) -> io::Result<Digest> {
+    H::hash_stream(TreeReader::new(path)?)
+}
+
+/// Adds the path an error occurred at to its message.
+fn error_at(path: &Path, error: io::Error) -> io::Error {
+    io::Error::new(error.kind(), format!("{}: {error}", path.display()))
+}
+
+/// Returns the bytes of a file name or link target.
+#[cfg(unix)]
+fn os_str_bytes(_path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+    use std::os::unix::ffi::OsStrExt;
+    Ok(name.as_bytes().to_vec())
+}
+
+/// Returns the bytes of a file name or link target, which must be valid Unicode to have a
+/// platform independent serialisation.
+#[cfg(not(unix))]
+fn os_str_bytes(path: &Path, name: &OsStr) -> io::Result<Vec<u8>> {
+    match name.to_str() {
+        Some(name) => Ok(name.as_bytes().to_vec()),
+        None => Err(error_at(
+            path,
+            io::Error::new(ErrorKind::InvalidData, "name is not valid Unicode"),
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o100 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &Metadata) -> bool {
+    false
+}
+
+impl TreeReader {
+    /// Starts serialising the tree at `path`, failing if its root can't be read.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut reader = Self {
+            buffer: MAGIC.to_vec(),
+            position: 0,
+            file: None,
+            directories: Vec::new(),
+        };
+        reader.push_node(path.as_ref())?;
+
+        Ok(reader)
+    }
+
+    fn push_length(&mut self, length: u64) {
+        self.buffer.extend_from_slice(&length.to_le_bytes());
+    }
+
+    /// Buffers the header of the node at `path`, opening it if it is a file or listing it if it
+    /// is a directory.
+    fn push_node(&mut self, path: &Path) -> io::Result<()> {
+        let metadata = fs::symlink_metadata(path).map_err(|e| error_at(path, e))?;
+
+        if metadata.is_symlink() {
+            let target = fs::read_link(path).map_err(|e| error_at(path, e))?;
+            let target = os_str_bytes(path, target.as_os_str())?;
+            self.buffer.push(b'l');
+            self.push_length(target.len() as u64);
+            self.buffer.extend_from_slice(&target);
+        } else if metadata.is_file() {
+            let file = File::open(path).map_err(|e| error_at(path, e))?;
+            self.buffer
+                .push(if is_executable(&metadata) { b'x' } else { b'f' });
+            self.push_length(metadata.len());
+            self.file = Some((file, path.to_path_buf(), metadata.len()));
+        } else if metadata.is_dir() {
+            let mut entries = Vec::new();
+            for entry in fs::read_dir(path).map_err(|e| error_at(path, e))? {
+                let entry = entry.map_err(|e| error_at(path, e))?;
+                let entry_path = entry.path();
+                entries.push((os_str_bytes(&entry_path, &entry.file_name())?, entry_path));
+            }
+            entries.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+            self.buffer.push(b'd');
+            self.push_length(entries.len() as u64);
+            self.directories.push(entries);
+        } else {
+            return Err(error_at(
+                path,
+                io::Error::new(ErrorKind::Unsupported, "unsupported file type"),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Buffers the next entry of the innermost unfinished directory, returning false once the
+    /// whole tree has been serialised.
+    fn push_next_entry(&mut self) -> io::Result<bool> {
+        while let Some(entries) = self.directories.last_mut() {
+            if let Some((name, path)) = entries.pop() {
+                self.push_length(name.len() as u64);
+                self.buffer.extend_from_slice(&name);
+                self.push_node(&path)?;
+                return Ok(true);
+            }
+            self.directories.pop();
+        }
+
+        Ok(false)
+    }
+}
+
+impl Read for TreeReader {
+    fn read(&mut self, output: &mut [u8]) -> io::Result<usize> {
+        if output.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if self.position < self.buffer.len() {
+                let length = output.len().min(self.buffer.len() - self.position);
+                output[..length]
+                    .copy_from_slice(&self.buffer[self.position..self.position + length]);
+                self.position += length;
+                return Ok(length);
+            }
+            self.buffer.clear();
+            self.position = 0;
+
+            if let Some((file, path, remaining)) = &mut self.file {
+                // The contents must match the size already serialised in the header.
+                let changed_size = || {
+                    error_at(
+                        path,
+                        io::Error::new(ErrorKind::InvalidData, "file changed size while read"),
+                    )
+                };
+                if *remaining > 0 {
+                    let length = output
+                        .len()
+                        .min(usize::try_from(*remaining).unwrap_or(usize::MAX));
+                    let bytes = file
+                        .read(&mut output[..length])
+                        .map_err(|e| error_at(path, e))?;
+                    if bytes == 0 {
+                        return Err(changed_size());
+                    }
+                    *remaining -= bytes as u64;
+                    return Ok(bytes);
+                }
+                if file.read(&mut [0]).map_err(|e| error_at(path, e))? != 0 {
+                    return Err(changed_size());
+                }
+                self.file = None;
+            }
+
+            if !self.push_next_entry()? {
+                return Ok(0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// Builds the serialisation of a node from its type, length and payload.
+    fn node(kind: u8, length: usize, payload: &[u8]) -> Vec<u8> {
+        let mut node = vec![kind];
+        node.extend_from_slice(&(length as u64).to_le_bytes());
+        node.extend_from_slice(payload);
+        node
+    }
+
+    fn entry(name: &str, node: Vec<u8>) -> Vec<u8> {
+        let mut entry = (name.len() as u64).to_le_bytes().to_vec();
+        entry.extend_from_slice(name.as_bytes());
+        entry.extend_from_slice(&node);
+        entry
+    }
+
+    fn serialise(path: impl AsRef<Path>) -> Vec<u8> {
+        let mut serialisation = Vec::new();
+        TreeReader::new(path)
+            .unwrap()
+            .read_to_end(&mut serialisation)
+            .unwrap();
+        serialisation
+    }
+
+    #[test]
+    fn file_serialisation() {
+        let path = format!("{}/tests/data/abc.txt", env!("CARGO_MANIFEST_DIR"));
+
+        assert_eq!(serialise(path), [MAGIC, &node(b'f', 3, b"abc")].concat());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tree_serialisation() {
+        let abc = || node(b'f', 3, b"abc");
+        let sub = [
+            node(b'd', 2, &[]),
+            entry("c.txt", abc()),
+            entry("deep", [node(b'd', 1, &[]), entry("d.txt", abc())].concat()),
+        ]
+        .concat();
+        let expected = [
+            MAGIC.to_vec(),
+            node(b'd', 7, &[]),
+            entry("a-b.txt", abc()),
+            entry("a.txt", abc()),
+            entry("b.log", abc()),
+            entry("file-link", node(b'l', 5, b"a.txt")),
+            entry("link", node(b'l', 3, b"sub")),
+            entry(
+                "other",
+                [node(b'd', 1, &[]), entry("e.txt", abc())].concat(),
+            ),
+            entry("sub", sub),
+        ]
+        .concat();
+
+        let tree = format!("{}/tests/data/tree", env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(serialise(&tree), expected);
+
+        // Reading in small pieces gives the same stream.
+        let mut reader = TreeReader::new(&tree).unwrap();
+        let mut serialisation = Vec::new();
+        let mut piece = [0; 7];
+        loop {
+            let bytes = reader.read(&mut piece).unwrap();
+            if bytes == 0 {
+                break;
+            }
+            serialisation.extend_from_slice(&piece[..bytes]);
+        }
+        assert_eq!(serialisation, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = env::temp_dir().join(format!("hashsum-tree-executable-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let script = root.join("script");
+        fs::write(&script, b"abc").unwrap();
+
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o644)).unwrap();
+        let plain = serialise(&root);
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o744)).unwrap();
+        let executable = serialise(&root);
+        // Only the owner's execute bit is part of the serialisation.
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let group_executable = serialise(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        let directory = |kind| {
+            [
+                MAGIC.to_vec(),
+                node(b'd', 1, &[]),
+                entry("script", node(kind, 3, b"abc")),
+            ]
+            .concat()
+        };
+        assert_eq!(plain, directory(b'f'));
+        assert_eq!(executable, directory(b'x'));
+        assert_eq!(group_executable, executable);
+    }
+
+    #[test]
+    fn missing_root() {
+        let error = TreeReader::new("does-not-exist").err().unwrap();
+
+        assert_eq!(error.kind(), ErrorKind::NotFound);
+        assert!(error.to_string().starts_with("does-not-exist: "));
+    }
+}
@@ -0,0 +1,270 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use crate::glob::Pattern;
+use std::{
+    ffi::{OsStr, OsString},
+    fs::{self, DirEntry, Metadata},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Options controlling which files '--recursive' finds in a directory.
+#[derive(Default)]
+pub struct WalkOptions {
+    /// Follow symbolic links found in directories rather than skipping them.
+    pub follow_symlinks: bool,
+    /// Skip directories on a different file system to the one walked.
+    pub one_file_system: bool,
+    /// Only files matching one of these patterns are returned, unless there are none.
+    pub include: Vec<Pattern>,
+    /// Files and directories matching any of these patterns are skipped.
+    pub exclude: Vec<Pattern>,
+}
+
+/// An error reading part of a directory tree.
+#[derive(Debug)]
+pub struct WalkError {
+    pub path: PathBuf,
+    pub error: io::Error,
+}
+
+/// Returns a function attaching `path` to an error.
+fn error_at(path: &Path) -> impl FnOnce(io::Error) -> WalkError {
+    let path = path.to_path_buf();
+    move |error| WalkError { path, error }
+}
+
+struct Walker<'a> {
+    options: &'a WalkOptions,
+    root_device: u64,
+    /// Canonical paths of the directories being walked, to detect symbolic link loops.
+    ancestors: Vec<PathBuf>,
+    files: Vec<Result<OsString, WalkError>>,
+}
+
+/// Returns the paths of the regular files in the directory `root` and its subdirectories.
+///
+/// Each path is `root` followed by the path within it, as printed by find. Entries of a
+/// directory are visited in byte order of their names, each subdirectory being walked where its
+/// name falls, so the order depends only on the names in the tree. Symbolic links are skipped
+/// unless `follow_symlinks` is set, though `root` itself is always followed. Names that aren't
+/// valid Unicode are matched against patterns with the invalid parts replaced by U+FFFD.
+///
+/// Errors reading part of the tree are returned in place of the files that couldn't be found,
+/// and the rest of the tree is still walked.
+pub fn walk(root: &OsStr, options: &WalkOptions) -> Vec<Result<OsString, WalkError>> {
+    let root_path = Path::new(root);
+    let metadata = match fs::metadata(root_path) {
+        Ok(metadata) => metadata,
+        Err(error) => {
+            return vec![Err(WalkError {
+                path: root_path.to_path_buf(),
+                error,
+            })];
+        }
+    };
+
+    let mut walker = Walker {
+        options,
+        root_device: device(&metadata),
+        ancestors: Vec::new(),
+        files: Vec::new(),
+    };
+    if let Err(error) = walker.walk_directory(root_path, "") {
+        walker.files.push(Err(error));
+    }
+
+    walker.files
+}
+
+impl Walker<'_> {
+    /// Walks `directory`, found at `relative_path` within the root. Returns an error if the
+    /// directory itself can't be read, while errors for its entries are recorded in `files`.
+    fn walk_directory(&mut self, directory: &Path, relative_path: &str) -> Result<(), WalkError> {
+        if self.options.follow_symlinks {
+            let canonical_path = fs::canonicalize(directory).map_err(error_at(directory))?;
+            if self.ancestors.contains(&canonical_path) {
+                return Err(WalkError {
+                    path: directory.to_path_buf(),
+                    error: io::Error::other("file system loop detected"),
+                });
+            }
+            self.ancestors.push(canonical_path);
+        }
+
+        let entries = fs::read_dir(directory)
+            .and_then(|entries| entries.collect::<io::Result<Vec<_>>>())
+            .map_err(error_at(directory));
+        let result = entries.map(|entries| self.walk_entries(entries, relative_path));
+
+        if self.options.follow_symlinks {
+            self.ancestors.pop();
+        }
+        result
+    }
+
+    /// Walks the entries of a directory found at `relative_path` within the root.
+    fn walk_entries(&mut self, mut entries: Vec<DirEntry>, relative_path: &str) {
+        entries.sort_by_key(|entry| entry.file_name());
+
+        for entry in entries {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let entry_relative_path = if relative_path.is_empty() {
+                name.clone()
+            } else {
+                format!("{relative_path}/{name}")
+            };
+
+            let metadata = if self.options.follow_symlinks {
+                fs::metadata(&path)
+            } else {
+                fs::symlink_metadata(&path)
+            };
+            let metadata = match metadata {
+                Ok(metadata) => metadata,
+                Err(error) => {
+                    self.files.push(Err(error_at(&path)(error)));
+                    continue;
+                }
+            };
+
+            if metadata.is_symlink()
+                || self
+                    .options
+                    .exclude
+                    .iter()
+                    .any(|pattern| pattern.matches(&entry_relative_path, &name))
+            {
+                continue;
+            }
+
+            if metadata.is_dir() {
+                if (!self.options.one_file_system || device(&metadata) == self.root_device)
+                    && let Err(error) = self.walk_directory(&path, &entry_relative_path)
+                {
+                    self.files.push(Err(error));
+                }
+            } else if metadata.is_file()
+                && (self.options.include.is_empty()
+                    || self
+                        .options
+                        .include
+                        .iter()
+                        .any(|pattern| pattern.matches(&entry_relative_path, &name)))
+            {
+                self.files.push(Ok(path.into_os_string()));
+            }
+        }
+    }
+}
+
+/// Returns the device holding the file described by `metadata`.
+#[cfg(unix)]
+fn device(metadata: &Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.dev()
+}
+
+/// Treats every file as being on the same file system where device numbers aren't available.
+#[cfg(not(unix))]
+fn device(_metadata: &Metadata) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn tree() -> OsString {
+        OsString::from(format!("{}/tests/data/tree", env!("CARGO_MANIFEST_DIR")))
+    }
+
+    fn walk_tree(options: &WalkOptions) -> Vec<String> {
+        let root = tree();
+        walk(&root, options)
+            .iter()
+            .map(|path| {
+                path.as_ref()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .strip_prefix(root.to_str().unwrap())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sorted_walk() {
+        assert_eq!(
+            walk_tree(&WalkOptions::default()),
+            [
+                "/a-b.txt",
+                "/a.txt",
+                "/b.log",
+                "/other/e.txt",
+                "/sub/c.txt",
+                "/sub/deep/d.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_symlinks() {
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            walk_tree(&options),
+            [
+                "/a-b.txt",
+                "/a.txt",
+                "/b.log",
+                "/file-link",
+                "/link/c.txt",
+                "/link/deep/d.txt",
+                "/other/e.txt",
+                "/sub/c.txt",
+                "/sub/deep/d.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn include_and_exclude() {
+        let options = WalkOptions {
+            include: vec![Pattern::new("*.txt")],
+            exclude: vec![Pattern::new("a*"), Pattern::new("sub/deep")],
+            ..Default::default()
+        };
+
+        assert_eq!(walk_tree(&options), ["/other/e.txt", "/sub/c.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_loop() {
+        let root = env::temp_dir().join(format!("hashsum-walk-loop-{}", std::process::id()));
+        fs::create_dir_all(root.join("a")).unwrap();
+        let _ = fs::remove_file(root.join("a/up"));
+        std::os::unix::fs::symlink("..", root.join("a/up")).unwrap();
+
+        let options = WalkOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        let mut result = walk(root.as_os_str(), &options);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(result.len(), 1);
+        let error = result.pop().unwrap().unwrap_err();
+        assert_eq!(error.path, root.join("a/up"));
+        assert_eq!(error.error.to_string(), "file system loop detected");
+    }
+}
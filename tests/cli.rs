@@ -0,0 +1,1047 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// SPDX-FileCopyrightText: Copyright 2025 Edward Scroop <edward.scroop@gmail.com>
+
+use std::{
+    io::{ErrorKind, Write},
+    path::Path,
+    process::{Command, Output, Stdio},
+};
+
+// Digest of the contents of tests/data/abc.txt for every supported algorithm.
+const ABC_VECTORS: [(&str, &str, &str); 19] = [
+    ("crc32c", "CRC32C", "364b3fb7"),
+    ("crc64", "CRC64", "2cd8094a1a277627"),
+    ("md5", "MD5", "900150983cd24fb0d6963f7d28e17f72"),
+    ("sha1", "SHA1", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+    (
+        "sha224",
+        "SHA224",
+        "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7",
+    ),
+    (
+        "sha256",
+        "SHA256",
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+    ),
+    (
+        "sha384",
+        "SHA384",
+        "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7",
+    ),
+    (
+        "sha512",
+        "SHA512",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+    ),
+    (
+        "sha512-224",
+        "SHA512/224",
+        "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa",
+    ),
+    (
+        "sha512-256",
+        "SHA512/256",
+        "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23",
+    ),
+    (
+        "sha3-224",
+        "SHA3-224",
+        "e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf",
+    ),
+    (
+        "sha3-256",
+        "SHA3-256",
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+    ),
+    (
+        "sha3-384",
+        "SHA3-384",
+        "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25",
+    ),
+    (
+        "sha3-512",
+        "SHA3-512",
+        "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+    ),
+    (
+        "shake128",
+        "SHAKE128",
+        "5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8",
+    ),
+    (
+        "shake256",
+        "SHAKE256",
+        "483366601360a8771c6863080cc4114d8db44530f8f1e1ee4f94ea37e78b5739d5a15bef186a5386c75744c0527e1faa9f8726e462a12a4feb06bd8801e751e4",
+    ),
+    (
+        "blake2b",
+        "BLAKE2b",
+        "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d17d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923",
+    ),
+    (
+        "blake2s",
+        "BLAKE2s",
+        "508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982",
+    ),
+    (
+        "blake3",
+        "BLAKE3",
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+    ),
+];
+
+/// Runs hashsum from the test data directory with `stdin` piped to it.
+fn hashsum(arguments: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hashsum"))
+        .args(arguments)
+        .current_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // hashsum doesn't read stdin unless it is an operand, and may exit before it is written.
+    if let Err(e) = child.stdin.take().unwrap().write_all(stdin) {
+        assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+    }
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn file_digest_for_every_algorithm() {
+    for (algorithm, tag, digest) in ABC_VECTORS {
+        let output = hashsum(&["--algorithm", algorithm, "abc.txt"], &[]);
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{tag} (abc.txt) = {digest}\n")
+        );
+    }
+}
+
+#[test]
+fn stdin_digest_for_every_algorithm() {
+    for (algorithm, tag, digest) in ABC_VECTORS {
+        let output = hashsum(&["--algorithm", algorithm], b"abc");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{tag} (-) = {digest}\n")
+        );
+    }
+}
+
+#[test]
+fn untagged_output() {
+    let output = hashsum(&["--algorithm=md5", "--untagged", "abc.txt", "-"], b"abc");
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "900150983cd24fb0d6963f7d28e17f72  abc.txt\n900150983cd24fb0d6963f7d28e17f72  -\n"
+    );
+}
+
+#[test]
+fn option_forms() {
+    let expected =
+        "900150983cd24fb0d6963f7d28e17f72  abc.txt\n900150983cd24fb0d6963f7d28e17f72  -\n";
+
+    for arguments in [
+        &["-a", "md5", "-u", "abc.txt", "-"][..],
+        &["-amd5", "-u", "abc.txt", "-"],
+        &["-ua", "md5", "abc.txt", "-"],
+        &["-uamd5", "abc.txt", "-"],
+        &["abc.txt", "--untag", "--algo=md5", "-"],
+        &["--algorithm", "md5", "--untagged", "abc.txt", "--", "-"],
+    ] {
+        let output = hashsum(arguments, b"abc");
+
+        assert!(output.status.success(), "{arguments:?}");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+}
+
+#[test]
+fn invalid_options() {
+    for (arguments, message) in [
+        (&["--bogus"][..], "unrecognised option '--bogus'"),
+        (&["-uy"], "invalid option -- 'y'"),
+        (&["-a"], "option requires an argument -- 'a'"),
+        (&["--length"], "option '--length' requires an argument"),
+        (
+            &["--check=yes"],
+            "option '--check' doesn't allow an argument",
+        ),
+        (
+            &["--h"],
+            "option '--h' is ambiguous; possibilities: '--hmac-key-file' '--help'",
+        ),
+        (&["--algorithm="], "invalid argument '' for '--algorithm'"),
+    ] {
+        let output = hashsum(arguments, &[]);
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .starts_with(&format!("hashsum: {message}\n")),
+            "{arguments:?}"
+        );
+    }
+}
+
+#[test]
+fn recursive() {
+    let output = hashsum(&["-r", "-a", "md5", "tree", "abc.txt"], &[]);
+
+    assert!(output.status.success());
+    let listing = String::from_utf8(output.stdout.clone()).unwrap();
+    let paths: Vec<&str> = listing
+        .lines()
+        .map(|line| {
+            line.strip_prefix("MD5 (")
+                .unwrap()
+                .split(')')
+                .next()
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "tree/a-b.txt",
+            "tree/a.txt",
+            "tree/b.log",
+            "tree/other/e.txt",
+            "tree/sub/c.txt",
+            "tree/sub/deep/d.txt",
+            "abc.txt",
+        ]
+    );
+
+    let output = hashsum(&["--check"], &output.stdout);
+    assert!(output.status.success());
+
+    let output = hashsum(
+        &[
+            "-rLu",
+            "-a",
+            "md5",
+            "--include=*.txt",
+            "--exclude",
+            "sub",
+            "--exclude=other/*",
+            "tree/",
+        ],
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "900150983cd24fb0d6963f7d28e17f72  tree/a-b.txt\n\
+         900150983cd24fb0d6963f7d28e17f72  tree/a.txt\n\
+         900150983cd24fb0d6963f7d28e17f72  tree/link/c.txt\n\
+         900150983cd24fb0d6963f7d28e17f72  tree/link/deep/d.txt\n"
+    );
+
+    let output = hashsum(&["--recursive", "--check", "tree"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn parallel_jobs() {
+    let sequential = hashsum(
+        &["-j1", "-r", "-a", "sha256", "tree", "abc.txt", "-"],
+        b"abc",
+    );
+    assert!(sequential.status.success());
+
+    for jobs in ["-j2", "-j3", "--jobs=8", "-j0"] {
+        let output = hashsum(
+            &[jobs, "-r", "-a", "sha256", "tree", "abc.txt", "-"],
+            b"abc",
+        );
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, sequential.stdout);
+    }
+
+    // Standard input is read by the first '-', leaving nothing for the second.
+    for _ in 0..10 {
+        let output = hashsum(&["-j4", "-a", "md5", "-", "abc.txt", "-"], b"abc");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "MD5 (-) = 900150983cd24fb0d6963f7d28e17f72\n\
+             MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+             MD5 (-) = d41d8cd98f00b204e9800998ecf8427e\n"
+        );
+    }
+
+    let output = hashsum(&["--jobs=many"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn buffer_size() {
+    let path = std::env::temp_dir().join(format!("hashsum-buffer-{}", std::process::id()));
+    let contents: Vec<u8> = (0..100_000).map(|i| (i % 251) as u8).collect();
+    std::fs::write(&path, &contents).unwrap();
+    let path = path.to_str().unwrap();
+
+    // The file is mapped at sizes below its own, and standard input is read in several reads.
+    let expected = format!(
+        "MD5 ({path}) = 28cb595c158e9b74e34ae9e8da710fff\n\
+         MD5 (-) = 28cb595c158e9b74e34ae9e8da710fff\n"
+    );
+    for size in [
+        "--buffer-size=1",
+        "--buffer-size=2",
+        "--buffer-size=1k",
+        "--buffer-size=1G",
+    ] {
+        let output = hashsum(&[size, "-a", "md5", path, "-"], &contents);
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+    std::fs::remove_file(path).unwrap();
+
+    for size in ["0", "-1", "K", "1T", "99999999999999999999G"] {
+        let output = hashsum(&["--buffer-size", size, "abc.txt"], &[]);
+        assert_eq!(output.status.code(), Some(1));
+    }
+}
+
+#[test]
+fn file_errors() {
+    // Every file is still hashed after a failure, which only changes the exit status.
+    for jobs in ["-j1", "-j4"] {
+        let output = hashsum(
+            &[jobs, "-a", "md5", "abc.txt", "missing", "tree", "abc.txt"],
+            &[],
+        );
+
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+             MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n"
+        );
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "hashsum: missing: No such file or directory\n\
+             hashsum: tree: Is a directory\n"
+        );
+    }
+
+    let output = hashsum(&["-r", "missing", "tree/sub/deep"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1219131554 3 tree/sub/deep/d.txt\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: missing: No such file or directory\n"
+    );
+}
+
+#[test]
+fn tree_digest() {
+    // Digests of the serialisation built independently with Python's hashlib.
+    let expected = "SHA256 (tree) = 42b08020d1b19d38000b9d34d632a2afa8193cd70633484d7d6705878567adea\n\
+                    SHA256 (abc.txt) = 5b9a635e631731ea8ea43652b8425290c7d0b74493801423ca9970dbed8a3dee\n";
+    let output = hashsum(&["--tree-digest", "-a", "sha256", "tree", "abc.txt"], &[]);
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout.clone()).unwrap(), expected);
+
+    let check = hashsum(&["--check", "--tree-digest"], &output.stdout);
+    assert!(check.status.success());
+    assert_eq!(
+        String::from_utf8(check.stdout).unwrap(),
+        "tree: OK\nabc.txt: OK\n"
+    );
+
+    // Errors serialising a tree already name the path they occurred at.
+    let output = hashsum(&["--tree-digest", "-a", "sha256", "missing"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: missing: No such file or directory\n"
+    );
+
+    let output = hashsum(
+        &["--check", "--tree-digest"],
+        format!("SHA256 (missing) = {}\n", "0".repeat(64)).as_bytes(),
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("hashsum: missing: No such file or directory\n")
+    );
+
+    for arguments in [
+        &["--tree-digest"][..],
+        &["--tree-digest", "-"],
+        &["--tree-digest", "-r", "tree"],
+    ] {
+        let output = hashsum(arguments, &[]);
+        assert_eq!(output.status.code(), Some(1));
+    }
+}
+
+#[test]
+fn check_round_trip_for_every_algorithm() {
+    for (algorithm, _, _) in ABC_VECTORS {
+        for layout in [
+            &[][..],
+            &["--untagged"],
+            &["--base64"],
+            &["--untagged", "--base64"],
+        ] {
+            let mut arguments = vec!["--algorithm", algorithm, "abc.txt"];
+            arguments.extend_from_slice(layout);
+            let output = hashsum(&arguments, &[]);
+            assert!(output.status.success());
+
+            let output = hashsum(&["--algorithm", algorithm, "--check"], &output.stdout);
+            assert!(output.status.success());
+            assert_eq!(String::from_utf8(output.stdout).unwrap(), "abc.txt: OK\n");
+        }
+    }
+}
+
+#[test]
+fn shake_length() {
+    let output = hashsum(&["--algorithm", "shake128", "--length=64", "abc.txt"], &[]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout.clone()).unwrap(),
+        "SHAKE128-64 (abc.txt) = 5881092dd818bf5c\n"
+    );
+
+    let output = hashsum(&["--check"], &output.stdout);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "abc.txt: OK\n");
+
+    // The default length is accepted but leaves the tag unchanged.
+    let output = hashsum(
+        &["--algorithm", "shake128", "--length", "256", "abc.txt"],
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "SHAKE128 (abc.txt) = 5881092dd818bf5cf8a3ddb793fbcba74097d5c526a6d35f97b83351940f2cc8\n"
+    );
+}
+
+#[test]
+fn blake2_length() {
+    let output = hashsum(&["--algorithm", "blake2b", "-l", "256", "abc.txt"], &[]);
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout.clone()).unwrap(),
+        "BLAKE2b-256 (abc.txt) = bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319\n"
+    );
+
+    let output = hashsum(&["--check"], &output.stdout);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "abc.txt: OK\n");
+
+    let output = hashsum(&["--algorithm", "blake2s", "-l128", "abc.txt"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "BLAKE2s-128 (abc.txt) = aa4938119b1dc7b87cbad0ffd200d0ae\n"
+    );
+}
+
+#[test]
+fn blake3_modes() {
+    let output = hashsum(
+        &[
+            "--algorithm",
+            "blake3",
+            "--key-file",
+            "blake3.key",
+            "abc.txt",
+        ],
+        &[],
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout.clone()).unwrap(),
+        "BLAKE3-KEYED (abc.txt) = 157f8b4b104070014ab0b3b7aff364f794e010e92b1c976318e892f380b53406\n"
+    );
+
+    // Checking keyed digests needs the same key, which selects BLAKE3 by itself.
+    let check = hashsum(&["--key-file=blake3.key", "--check"], &output.stdout);
+    assert!(check.status.success());
+    assert_eq!(String::from_utf8(check.stdout).unwrap(), "abc.txt: OK\n");
+
+    // Without the key the line is in the wrong mode rather than a mismatch.
+    let check = hashsum(&["--check", "--warn"], &output.stdout);
+    assert_eq!(check.status.code(), Some(1));
+    assert!(check.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(check.stderr).unwrap(),
+        "hashsum: standard input: 1: improperly formatted checksum line\n\
+         hashsum: standard input: no properly formatted checksum lines found\n\
+         hashsum: WARNING: 1 line is improperly formatted\n"
+    );
+
+    let output = hashsum(
+        &[
+            "--algorithm",
+            "blake3",
+            "--derive-key=hashsum 2025-01-01 test context",
+            "abc.txt",
+        ],
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "BLAKE3-DERIVE (abc.txt) = f65bdd7ad3b468880a9ac4bc706bc30c6330c2c0e783780351ded9811e99244f\n"
+    );
+
+    let check = hashsum(
+        &["--derive-key=hashsum 2025-01-01 test context", "--check"],
+        b"BLAKE3-DERIVE (abc.txt) = f65bdd7ad3b468880a9ac4bc706bc30c6330c2c0e783780351ded9811e99244f\n\
+          BLAKE3 (abc.txt) = 6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85\n",
+    );
+    assert!(check.status.success());
+    assert_eq!(
+        String::from_utf8(check.stdout).unwrap(),
+        "abc.txt: OK\nabc.txt: OK\n"
+    );
+
+    let output = hashsum(&["--algorithm", "blake3", "-l", "512", "abc.txt"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "BLAKE3-512 (abc.txt) = 6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d851fb250ae7393f5d02813b65d521a0d492d9ba09cf7ce7f4cffd900f23374bf0b\n"
+    );
+}
+
+#[test]
+fn invalid_blake3_mode() {
+    for arguments in [
+        &["--algorithm", "md5", "--key-file", "blake3.key"][..],
+        &["--algorithm", "blake3", "--key-file", "abc.txt"],
+        &["--algorithm", "blake3", "--key-file", "missing.key"],
+        &[
+            "--algorithm",
+            "blake3",
+            "--key-file",
+            "blake3.key",
+            "--derive-key",
+            "context",
+        ],
+    ] {
+        let output = hashsum(arguments, b"abc");
+
+        assert_eq!(output.status.code(), Some(1));
+    }
+}
+
+#[test]
+fn hmac() {
+    // Values from Python's hmac module.
+    let output = hashsum(
+        &[
+            "--algorithm=sha256",
+            "--hmac-key-file",
+            "hmac.key",
+            "abc.txt",
+        ],
+        &[],
+    );
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout.clone()).unwrap(),
+        "HMAC-SHA256 (abc.txt) = 9946dad4e00e913fc8be8e5d3f7e110a4a9e832f83fb09c345285d78638d8a0e\n"
+    );
+
+    // HMAC lines can only be checked with the key, and the tag selects the algorithm.
+    let output_with_key = hashsum(&["--hmac-key-file=hmac.key", "--check"], &output.stdout);
+    assert!(output_with_key.status.success());
+    assert_eq!(
+        String::from_utf8(output_with_key.stdout).unwrap(),
+        "abc.txt: OK\n"
+    );
+    let output = hashsum(&["--check"], &output.stdout);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = hashsum(
+        &[
+            "--algorithm=md5",
+            "--hmac-key-file=hmac.key",
+            "--untagged",
+            "abc.txt",
+        ],
+        &[],
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout.clone()).unwrap(),
+        "d9bf7c3a63eae7031c4e6d7c9b78ba93  abc.txt\n"
+    );
+    let output = hashsum(
+        &["--algorithm=md5", "--hmac-key-file=hmac.key", "--check"],
+        &output.stdout,
+    );
+    assert!(output.status.success());
+
+    let output = hashsum(
+        &[
+            "--algorithm=blake2b",
+            "--length=256",
+            "--hmac-key-file=hmac.key",
+            "-",
+        ],
+        b"abc",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "HMAC-BLAKE2b-256 (-) = b42894c3e2b8dec04c3232bcd8b2c8f0ca5292ac0152697160c777e2cc442501\n"
+    );
+}
+
+#[test]
+fn invalid_hmac() {
+    for arguments in [
+        &["--hmac-key-file", "hmac.key"][..],
+        &["--algorithm", "crc64", "--hmac-key-file", "hmac.key"],
+        &["--algorithm", "md5", "--hmac-key-file", "missing.key"],
+        &["--algorithm", "md5", "--hmac-key-file"],
+        &[
+            "--algorithm",
+            "blake3",
+            "--key-file",
+            "blake3.key",
+            "--hmac-key-file",
+            "hmac.key",
+        ],
+    ] {
+        let output = hashsum(arguments, b"abc");
+
+        assert_eq!(output.status.code(), Some(1));
+    }
+}
+
+#[test]
+fn invalid_length() {
+    for arguments in [
+        &["--algorithm", "shake128", "--length=12"][..],
+        &["--algorithm", "shake128", "--length=65544"],
+        &["--algorithm", "shake128", "--length=abc"],
+        &["--algorithm", "blake2b", "-l", "520"],
+        &["--algorithm", "blake2s", "-l", "264"],
+        &["--algorithm", "sha256", "--length=64"],
+    ] {
+        let output = hashsum(arguments, b"abc");
+
+        assert_eq!(output.status.code(), Some(1));
+    }
+}
+
+#[test]
+fn legacy_checksums() {
+    // Output from GNU coreutils cksum, which leaves out the filename when no files are given.
+    for (arguments, expected) in [
+        (&["abc.txt"][..], "1219131554 3 abc.txt\n"),
+        (&[], "1219131554 3\n"),
+        (&["-"], "1219131554 3 -\n"),
+        (&["--algorithm=crc", "abc.txt"], "1219131554 3 abc.txt\n"),
+        (&["--algorithm=crc32b", "abc.txt"], "891568578 3 abc.txt\n"),
+        (&["--algorithm=crc32b"], "891568578 3\n"),
+        (&["--algorithm=bsd", "abc.txt"], "16556     1 abc.txt\n"),
+        (&["--algorithm=bsd"], "16556     1\n"),
+        (&["--algorithm=sysv", "abc.txt"], "294 1 abc.txt\n"),
+        (&["--algorithm=sysv"], "294 1\n"),
+    ] {
+        let output = hashsum(arguments, b"abc");
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+}
+
+#[test]
+fn legacy_checksums_not_checkable() {
+    let output = hashsum(&["--algorithm=crc", "--check"], b"1219131554 3 abc.txt\n");
+    assert_eq!(output.status.code(), Some(1));
+
+    // Untagged lines need an algorithm when the default CRC is used.
+    let output = hashsum(&["--check"], b"900150983cd24fb0d6963f7d28e17f72  abc.txt\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: standard input: no properly formatted checksum lines found\n\
+         hashsum: WARNING: 1 line is improperly formatted\n"
+    );
+}
+
+#[test]
+fn check_mismatch() {
+    let output = hashsum(
+        &["--check"],
+        b"MD5 (abc.txt) = 00000000000000000000000000000000\n",
+    );
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "abc.txt: FAILED\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: WARNING: 1 computed checksum did NOT match\n"
+    );
+}
+
+#[test]
+fn check_output_options() {
+    let checksums = b"MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+                      not a checksum line\n\
+                      MD5 (abc.txt) = 00000000000000000000000000000000\n";
+
+    let output = hashsum(&["--check", "--quiet"], checksums);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "abc.txt: FAILED\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: WARNING: 1 line is improperly formatted\n\
+         hashsum: WARNING: 1 computed checksum did NOT match\n"
+    );
+
+    let output = hashsum(&["--check", "--status"], checksums);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    // The last of '--quiet', '--status' and '--warn' is used.
+    let output = hashsum(&["--check", "--status", "-w"], checksums);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "abc.txt: OK\nabc.txt: FAILED\n"
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: standard input: 2: improperly formatted checksum line\n\
+         hashsum: WARNING: 1 line is improperly formatted\n\
+         hashsum: WARNING: 1 computed checksum did NOT match\n"
+    );
+}
+
+#[test]
+fn check_strict() {
+    let checksums = b"MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\nnot a checksum line\n";
+
+    let output = hashsum(&["--check"], checksums);
+    assert!(output.status.success());
+
+    let output = hashsum(&["--check", "--strict"], checksums);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "abc.txt: OK\n");
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: WARNING: 1 line is improperly formatted\n"
+    );
+}
+
+#[test]
+fn check_ignore_missing() {
+    let checksums = b"MD5 (missing) = 900150983cd24fb0d6963f7d28e17f72\n\
+                      MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n";
+
+    let output = hashsum(&["--check"], checksums);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "missing: FAILED open or read\nabc.txt: OK\n"
+    );
+
+    let output = hashsum(&["--check", "--ignore-missing"], checksums);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "abc.txt: OK\n");
+    assert!(output.stderr.is_empty());
+
+    // Checking nothing at all is still a failure.
+    let output = hashsum(
+        &["--check", "--ignore-missing"],
+        b"MD5 (missing) = 900150983cd24fb0d6963f7d28e17f72\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: standard input: no file was verified\n"
+    );
+}
+
+#[test]
+fn check_options_need_check() {
+    for option in ["--ignore-missing", "--quiet", "--status", "--strict", "-w"] {
+        let output = hashsum(&[option, "abc.txt"], &[]);
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        let long = if option == "-w" { "--warn" } else { option };
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .starts_with(&format!(
+                    "hashsum: the {long} option is meaningful only when checking checksums\n"
+                ))
+        );
+    }
+}
+
+#[test]
+fn zero_terminated() {
+    let output = hashsum(&["-z", "-a", "md5", "abc.txt", "tree/a.txt"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\0\
+         MD5 (tree/a.txt) = 900150983cd24fb0d6963f7d28e17f72\0"
+    );
+
+    let output = hashsum(&["--zero", "abc.txt"], &[]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1219131554 3 abc.txt\0"
+    );
+
+    let output = hashsum(&["--check", "-z"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("hashsum: the --zero option is not supported when checking checksums\n")
+    );
+}
+
+#[test]
+fn files0_from() {
+    // The last name may be left unterminated.
+    for list in [&b"abc.txt\0tree/a.txt\0"[..], b"abc.txt\0tree/a.txt"] {
+        let output = hashsum(&["-a", "md5", "--files0-from=-"], list);
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+             MD5 (tree/a.txt) = 900150983cd24fb0d6963f7d28e17f72\n"
+        );
+    }
+
+    // An empty list hashes nothing rather than standard input.
+    let output = hashsum(&["--files0-from=-"], &[]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    // Entries that can't name a file are reported and the others still hashed.
+    for (list, message) in [
+        (
+            &b"\0abc.txt\0"[..],
+            "hashsum: -:1: invalid zero-length file name\n",
+        ),
+        (
+            b"-\0abc.txt",
+            "hashsum: -:1: when reading file names from standard input, no file name of '-' \
+             allowed\n",
+        ),
+    ] {
+        let output = hashsum(&["-a", "md5", "--files0-from=-"], list);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n"
+        );
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), message);
+    }
+
+    let output = hashsum(&["--check", "--files0-from=-"], b"\0\0");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: -:1: invalid zero-length file name\n\
+         hashsum: -:2: invalid zero-length file name\n"
+    );
+
+    let output = hashsum(&["--files0-from=missing"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "hashsum: missing: No such file or directory\n"
+    );
+
+    let output = hashsum(&["--files0-from=-", "abc.txt"], &[]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("hashsum: extra operand 'abc.txt'\n")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn special_file_names() {
+    use std::{ffi::OsStr, fs, os::unix::ffi::OsStrExt};
+
+    let directory = std::env::temp_dir().join(format!("hashsum-names-{}", std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let directory_name = directory.to_str().unwrap();
+    let mut list = Vec::new();
+    for name in [&b"a\nb"[..], b"c\\d", b"caf\xe9"] {
+        let path = directory.join(OsStr::from_bytes(name));
+        fs::write(&path, "abc").unwrap();
+        list.extend_from_slice(path.as_os_str().as_bytes());
+        list.push(0);
+    }
+
+    let listed = hashsum(&["-a", "md5", "--files0-from=-"], &list);
+    let recursive = hashsum(&["-a", "md5", "-r", directory_name], &[]);
+    let zero = hashsum(&["-a", "md5", "-z", "--files0-from=-"], &list);
+    let check = hashsum(&["--check"], &listed.stdout);
+    fs::remove_dir_all(&directory).unwrap();
+
+    let path = |name: &[u8]| [directory_name.as_bytes(), b"/", name].concat();
+    let line = |escape: &[u8], name: &[u8], end: &[u8]| {
+        [
+            escape,
+            b"MD5 (",
+            &path(name),
+            b") = 900150983cd24fb0d6963f7d28e17f72",
+            end,
+        ]
+        .concat()
+    };
+
+    // Names with newlines or backslashes are escaped, while other bytes are printed as they are.
+    let escaped = [
+        line(b"\\", b"a\\nb", b"\n"),
+        line(b"\\", b"c\\\\d", b"\n"),
+        line(b"", b"caf\xe9", b"\n"),
+    ]
+    .concat();
+    assert!(listed.status.success());
+    assert_eq!(listed.stdout, escaped);
+    assert_eq!(recursive.stdout, escaped);
+
+    // Lines ending in NUL need no escaping.
+    assert_eq!(
+        zero.stdout,
+        [
+            line(b"", b"a\nb", b"\0"),
+            line(b"", b"c\\d", b"\0"),
+            line(b"", b"caf\xe9", b"\0"),
+        ]
+        .concat()
+    );
+
+    assert!(check.status.success());
+    assert_eq!(
+        check.stdout,
+        [
+            &b"\\"[..],
+            &path(b"a\\nb"),
+            b": OK\n\\",
+            &path(b"c\\\\d"),
+            b": OK\n",
+            &path(b"caf\xe9"),
+            b": OK\n",
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn several_algorithms() {
+    let expected = "MD5 (abc.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+                    SHA1 (abc.txt) = a9993e364706816aba3e25717850c26c9cd0d89d\n\
+                    BLAKE2s-128 (abc.txt) = aa4938119b1dc7b87cbad0ffd200d0ae\n\
+                    MD5 (tree/a.txt) = 900150983cd24fb0d6963f7d28e17f72\n\
+                    SHA1 (tree/a.txt) = a9993e364706816aba3e25717850c26c9cd0d89d\n\
+                    BLAKE2s-128 (tree/a.txt) = aa4938119b1dc7b87cbad0ffd200d0ae\n";
+
+    // Lists and repeated options combine, keeping the first of any duplicates.
+    for arguments in [
+        &["-a", "md5,sha1,blake2s", "-l128", "abc.txt", "tree/a.txt"][..],
+        &[
+            "-a",
+            "md5",
+            "-a",
+            "sha1,md5",
+            "-a",
+            "blake2s",
+            "-l128",
+            "-j1",
+            "abc.txt",
+            "tree/a.txt",
+        ],
+    ] {
+        let output = hashsum(arguments, &[]);
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+
+    // Standard input is also only read once.
+    let output = hashsum(&["-a", "md5,sha1"], b"abc");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "MD5 (-) = 900150983cd24fb0d6963f7d28e17f72\n\
+         SHA1 (-) = a9993e364706816aba3e25717850c26c9cd0d89d\n"
+    );
+
+    let check = hashsum(&["--check"], expected.as_bytes());
+    assert!(check.status.success());
+
+    for (arguments, message) in [
+        (
+            &["-a", "md5,crc", "abc.txt"][..],
+            "hashsum: --algorithm={bsd,sysv,crc,crc32b} can't be combined with other algorithms\n",
+        ),
+        (
+            &["-a", "md5,sha1", "--check"],
+            "hashsum: --check is not supported with several algorithms\n",
+        ),
+        (
+            &["-a", "md5,bogus", "abc.txt"],
+            "hashsum: invalid argument 'bogus' for '--algorithm'\n",
+        ),
+    ] {
+        let output = hashsum(arguments, &[]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .starts_with(message),
+            "{arguments:?}"
+        );
+    }
+}